@@ -1,9 +1,34 @@
 use super::parse::CallDef;
-use quote::quote;
+use quote::{format_ident, quote};
 
 /// See the `fn call` docs at the `lib.rs` of this crate for a high level definition.
 pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
-	let CallDef { pallet_struct, methods } = def;
+	let CallDef { pallet_struct, instance, methods } = def;
+
+	// The generics of `Call` and of the implementations for it. An instantiable pallet gets the
+	// instance as a second parameter, which defaults to `()` so that the default instance can be
+	// named `Call<T>`.
+	let (decl_generics, impl_generics, type_generics) = match &instance {
+		Some(i) => (
+			quote! { T: Config<#i>, #i: 'static = () },
+			quote! { T: Config<#i>, #i: 'static },
+			quote! { T, #i },
+		),
+		None => (quote! { T: Config }, quote! { T: Config }, quote! { T }),
+	};
+
+	// `Call` must use `T`, and the instance of an instantiable pallet, even when no argument does,
+	// so it gets an extra variant holding them which can never be built.
+	let ignore_variant = match &instance {
+		Some(i) => quote! {
+			#[doc(hidden)]
+			__Ignore(core::marker::PhantomData<(T, #i)>, core::convert::Infallible),
+		},
+		None => quote! {
+			#[doc(hidden)]
+			__Ignore(core::marker::PhantomData<T>, core::convert::Infallible),
+		},
+	};
 
 	// This is a vector of all the callable function names.
 	let fn_name = methods.iter().map(|method| &method.name).collect::<Vec<_>>();
@@ -23,6 +48,49 @@ pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
 		.map(|method| method.args.iter().map(|(_, type_)| type_.clone()).collect::<Vec<_>>())
 		.collect::<Vec<_>>();
 
+	// Signed calls get the `caller` of the extrinsic, inherents are dispatched without one. Each
+	// kind of call is rejected when dispatched as the other kind.
+	let signed_dispatch = methods
+		.iter()
+		.zip(&args_name)
+		.map(|(method, args_name)| {
+			let fn_name = &method.name;
+			if method.inherent {
+				quote! {
+					Call::#fn_name { .. } => return Err("Inherents cannot be dispatched by a signed extrinsic"),
+				}
+			} else {
+				quote! {
+					Call::#fn_name { #( #args_name ),* } => {
+						self.#fn_name(
+							// Note that we assume the first argument of every call is the `caller`.
+							caller,
+							#( #args_name ),*
+						)?;
+					},
+				}
+			}
+		})
+		.collect::<Vec<_>>();
+	let inherent_dispatch = methods
+		.iter()
+		.zip(&args_name)
+		.map(|(method, args_name)| {
+			let fn_name = &method.name;
+			if method.inherent {
+				quote! {
+					Call::#fn_name { #( #args_name ),* } => {
+						self.#fn_name(#( #args_name ),*)?;
+					},
+				}
+			} else {
+				quote! {
+					Call::#fn_name { .. } => return Err("Call is not an inherent"),
+				}
+			}
+		})
+		.collect::<Vec<_>>();
+
 	// This quote block creates an `enum Call` which contains all the calls exposed by our pallet,
 	// and the `Dispatch` trait logic to route a `caller` to access those functions.
 	let dispatch_impl = quote! {
@@ -30,35 +98,126 @@ pub fn expand_call(def: CallDef) -> proc_macro2::TokenStream {
 		//
 		// The parsed function names will be `snake_case`, and that will show up in the enum.
 		#[allow(non_camel_case_types)]
-		pub enum Call<T: Config> {
+		pub enum Call<#decl_generics> {
 			#(
 				#fn_name { #( #args_name: #args_type),* },
 			)*
+			#ignore_variant
 		}
 
 		// Dispatch logic at the pallet level, mapping each of the items in the `Call` enum to the
 		// appropriate function call with all arguments, including the `caller`.
-		impl<T: Config> crate::support::Dispatch for #pallet_struct<T> {
+		impl<#impl_generics> crate::support::Dispatch for #pallet_struct<#type_generics> {
 			type Caller = T::AccountId;
-			type Call = Call<T>;
+			type Call = Call<#type_generics>;
 
+			#[allow(unused_variables, unreachable_code)]
 			fn dispatch(&mut self, caller: Self::Caller, call: Self::Call) -> crate::support::DispatchResult {
 				match call {
+					#( #signed_dispatch )*
+				}
+				Ok(())
+			}
+		}
+
+		// Dispatch logic for the inherents of this pallet.
+		impl<#impl_generics> crate::support::DispatchInherent for #pallet_struct<#type_generics> {
+			type Call = Call<#type_generics>;
+
+			#[allow(unreachable_code)]
+			fn dispatch_inherent(&mut self, call: Self::Call) -> crate::support::DispatchResult {
+				match call {
+					#( #inherent_dispatch )*
+				}
+				Ok(())
+			}
+		}
+	};
+
+	// The bindings for the arguments of the second call when comparing two calls.
+	let other_args_name = args_name
+		.iter()
+		.map(|names| names.iter().map(|name| format_ident!("other_{}", name)).collect::<Vec<_>>())
+		.collect::<Vec<_>>();
+
+	// The index of each call, written first in its encoding. A `u8` is enough for any pallet.
+	let fn_index = (0..methods.len()).map(|index| index as u8).collect::<Vec<_>>();
+
+	// Every argument type, to bound the trait implementations below.
+	let all_args_type = args_type.iter().flatten().collect::<Vec<_>>();
+
+	// This quote block implements the common traits on `Call`. A `#[derive]` would require `T` to
+	// implement them, while only the argument types need to, so we write the implementations out.
+	// Calls can then be stored, compared, printed, and encoded to be referred to by their hash.
+	let traits_impl = quote! {
+		impl<#impl_generics> Clone for Call<#type_generics> where #( #all_args_type: Clone ),* {
+			fn clone(&self) -> Self {
+				match self {
+					#(
+						Call::#fn_name { #( #args_name ),* } => Call::#fn_name {
+							#( #args_name: #args_name.clone() ),*
+						},
+					)*
+					Call::__Ignore(_, never) => match *never {},
+				}
+			}
+		}
+
+		impl<#impl_generics> PartialEq for Call<#type_generics> where #( #all_args_type: PartialEq ),* {
+			fn eq(&self, other: &Self) -> bool {
+				#[allow(unreachable_patterns)]
+				match (self, other) {
+					#(
+						(
+							Call::#fn_name { #( #args_name ),* },
+							Call::#fn_name { #( #args_name: #other_args_name ),* },
+						) => true #( && #args_name == #other_args_name )*,
+					)*
+					(Call::__Ignore(_, never), _) | (_, Call::__Ignore(_, never)) => match *never {},
+					_ => false,
+				}
+			}
+		}
+
+		impl<#impl_generics> Eq for Call<#type_generics> where #( #all_args_type: Eq ),* {}
+
+		impl<#impl_generics> core::fmt::Debug for Call<#type_generics> where #( #all_args_type: core::fmt::Debug ),* {
+			fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+				match self {
+					#(
+						Call::#fn_name { #( #args_name ),* } => f
+							.debug_struct(stringify!(#fn_name))
+							#( .field(stringify!(#args_name), #args_name) )*
+							.finish(),
+					)*
+					Call::__Ignore(_, never) => match *never {},
+				}
+			}
+		}
+
+		impl<#impl_generics> crate::support::codec::Encode for Call<#type_generics>
+		where
+			#( #all_args_type: crate::support::codec::Encode ),*
+		{
+			// The buffer is not named `dest`, which a call argument can be named too.
+			fn encode_to(&self, __dest: &mut Vec<u8>) {
+				match self {
 					#(
 						Call::#fn_name { #( #args_name ),* } => {
-							self.#fn_name(
-								// Note that we assume the first argument of every call is the `caller`.
-								caller,
-								#( #args_name ),*
-							)?;
+							__dest.push(#fn_index);
+							#( crate::support::codec::Encode::encode_to(#args_name, __dest); )*
 						},
 					)*
+					Call::__Ignore(_, never) => match *never {},
 				}
-				Ok(())
 			}
 		}
 	};
 
 	// Return the generated code.
-	dispatch_impl.into()
+	quote! {
+		#dispatch_impl
+		#traits_impl
+	}
+	.into()
 }
\ No newline at end of file
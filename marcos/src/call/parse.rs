@@ -14,6 +14,9 @@ pub struct CallDef {
 	/// This is the name of the pallet struct where the callable functions are implemented. We
 	/// mostly assume it is `Pallet`.
 	pub pallet_struct: syn::Ident,
+	/// This is the instance parameter of the pallet, like `I` in `impl<T: Config<I>, I: 'static>
+	/// Pallet<T, I>`, for pallets that can be included more than once in a runtime.
+	pub instance: Option<syn::Ident>,
 	/// This is a list of the callable functions exposed by this pallet. See `CallVariantDef`.
 	pub methods: Vec<CallVariantDef>,
 }
@@ -25,6 +28,14 @@ pub struct CallVariantDef {
 	pub name: syn::Ident,
 	/// Information on args of the function: `(name, type)`.
 	pub args: Vec<(syn::Ident, Box<syn::Type>)>,
+	/// Whether the function is marked `#[inherent]`. Inherents are included by the block author
+	/// instead of being signed, so they have no `caller` argument.
+	pub inherent: bool,
+}
+
+/// Whether `attr` is the `#[inherent]` attribute.
+pub fn is_inherent_attr(attr: &syn::Attribute) -> bool {
+	attr.path().is_ident("inherent")
 }
 
 impl CallDef {
@@ -43,6 +54,17 @@ impl CallDef {
 			_ => panic!("not supported tokens"),
 		};
 
+		// A second type parameter is the instance of the pallet. We expect it to be declared as
+		// `I: 'static`, and the first one to be `T: Config<I>`.
+		let instance = match item_impl.generics.type_params().nth(1) {
+			Some(param) if item_impl.generics.type_params().count() == 2 => Some(param.ident.clone()),
+			Some(param) => {
+				let msg = "Invalid pallet::call, expected `impl<T: Config>` or `impl<T: Config<I>, I: 'static>`";
+				return Err(syn::Error::new(param.span(), msg))
+			},
+			None => None,
+		};
+
 		// Here is where we will store all the callable functions.
 		let mut methods = vec![];
 		for item in item_impl.items {
@@ -59,23 +81,31 @@ impl CallDef {
 					},
 				}
 
-				// The second argument should be the `caller: T::AccountId` argument.
-				match method.sig.inputs.iter().skip(1).next() {
-					Some(syn::FnArg::Typed(arg)) => {
-						// Here we specifically check that this argument is as we expect for
-						// `caller: T::AccountId`.
-						check_caller_arg(arg)?;
-					},
-					_ => {
-						let msg = "Invalid call, second argument should be `caller: T::AccountId`";
-						return Err(syn::Error::new(method.sig.span(), msg))
-					},
+				let inherent = method.attrs.iter().any(is_inherent_attr);
+
+				// The second argument should be the `caller: T::AccountId` argument, except for
+				// inherents which have no caller.
+				if !inherent {
+					match method.sig.inputs.iter().skip(1).next() {
+						Some(syn::FnArg::Typed(arg)) => {
+							// Here we specifically check that this argument is as we expect for
+							// `caller: T::AccountId`.
+							check_caller_arg(arg)?;
+						},
+						_ => {
+							let msg =
+								"Invalid call, second argument should be `caller: T::AccountId`";
+							return Err(syn::Error::new(method.sig.span(), msg))
+						},
+					}
 				}
 
 				let fn_name = method.sig.ident.clone();
 
-				// Parsing the rest of the args. Skipping 2 for `self` and `caller`.
-				for arg in method.sig.inputs.iter().skip(2) {
+				// Parsing the rest of the args. Skipping 2 for `self` and `caller`, or only `self`
+				// for inherents.
+				let skip = if inherent { 1 } else { 2 };
+				for arg in method.sig.inputs.iter().skip(skip) {
 					// All arguments should be typed.
 					let arg = if let syn::FnArg::Typed(arg) = arg {
 						arg
@@ -96,12 +126,12 @@ impl CallDef {
 				}
 
 				// Store all the function name and the arg data for the function.
-				methods.push(CallVariantDef { name: fn_name, args });
+				methods.push(CallVariantDef { name: fn_name, args, inherent });
 			}
 		}
 
 		// Return all callable functions for this pallet.
-		Ok(Self { pallet_struct, methods })
+		Ok(Self { pallet_struct, instance, methods })
 	}
 }
 
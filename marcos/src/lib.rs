@@ -11,20 +11,50 @@ pub fn call(
 
 /// Expand the `Runtime` definition.
 ///
+/// The first field must be `system`, and every field must have the type of a pallet, like
+/// `balances::Pallet<Runtime>`: the `Config` of the system pallet, and the `Call` and `Event` of
+/// each pallet, are found in the module of its type. A field of another type is a compile error.
+///
+/// The types the blocks are made of can be given as arguments, like
+/// `#[runtime(block = types::Block, extrinsic = support::Extrinsic)]`. These are the defaults. The
+/// extrinsic must be a struct with `caller` and `call` fields.
+///
 /// This generates function implementations on `Runtime`:
 /// - `fn new()` - which generates a new instance of the runtime, by instantiating all the pallets
 ///   included in the runtime.
 /// - `fn execute_block()` - which handles basic logic for executing a block of extrinsics. It does
-///   basic actions like incrementing the block number and checking the block to be executed has a
-///   valid block number.
+///   basic actions like incrementing the block number, clearing the events of the previous block
+///   and checking the block to be executed has a valid block number. Each extrinsic is dispatched
+///   in its own storage transaction, which is rolled back if the extrinsic fails. Extrinsics
+///   signed by the `RootAccount` of the system pallet fail, as only the runtime dispatches as root.
+///   The `support::traits::Hooks` of every pallet are called in the order the pallets are
+///   declared: `on_initialize` before the inherents, `check_inherents` after them, then `on_idle`
+///   with the weight left in the block after the extrinsics, and `on_finalize` last. The weight of
+///   the hooks and of each extrinsic is accounted for in the system pallet. The whole block runs
+///   in a storage transaction too, so a block with an invalid block number or inherent leaves no
+///   changes behind.
 ///
 /// This also generates code needed for dispatching calls to the pallets:
-/// - Note: For simplicity, we assume that the system pallet is not callable.
 /// - `enum RuntimeCall` - an "outer"-enum representing the accumulation of all possible calls to
-///   all pallets. The system pallet is not included.
+///   all pallets, including the system pallet. A field marked `#[no_call]` is left out, so a
+///   pallet which only provides storage and hooks needs no `Call`, and a runtime can keep the
+///   calls of the system pallet out. Each variant is named after the field of its pallet, and
+///   holds the `Call` found next to the type of the field, with the same generics: a field
+///   `balances::Pallet<Runtime, Instance1>` gets `balances::Call<Runtime, Instance1>`. This is how
+///   a runtime includes several instances of a pallet.
 /// - implements the trait `support::Dispatch` to dispatch calls to the appropriate pallet. Basic
-///   logic like incrementing the nonce of the user is included in the generated code. The system
-///   pallet is not included.
+///   logic like incrementing the nonce of the user is included in the generated code.
+/// - implements the trait `support::DispatchInherent` to dispatch the inherents of a block, which
+///   are `RuntimeCall`s of functions marked `#[inherent]`. They are dispatched before the
+///   extrinsics, and a failing inherent fails the whole block.
+/// - implements the trait `support::Dispatchable` on `RuntimeCall`, so that pallets can dispatch
+///   calls they receive, like the calls of a batch.
+/// - implements `support::codec::Encode` on `RuntimeCall`, so that calls can be referred to by a
+///   hash which is stable across builds.
+/// - `enum RuntimeEvent` - an "outer"-enum representing the accumulation of all events of all
+///   pallets, with a `From` implementation for each pallet's `Event`, found like its `Call`. The
+///   system pallet is not included, nor is a field marked `#[no_event]`, so a pallet which
+///   deposits no events needs no `Event`.
 #[proc_macro_attribute]
 pub fn runtime(
 	attr: proc_macro::TokenStream,
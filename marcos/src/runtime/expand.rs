@@ -1,14 +1,45 @@
-use super::parse::RuntimeDef;
-use quote::quote;
+use super::parse::{RuntimeAttr, RuntimeDef};
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
 
 /// See the `fn runtime` docs at the `lib.rs` of this crate for a high level definition.
 pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
-	let RuntimeDef { runtime_struct, pallets } = def;
+	let RuntimeDef { runtime_struct, pallets, system, attr } = def;
+	let RuntimeAttr { block, extrinsic } = attr;
+
+	// The module of the system pallet, whose `Config` the runtime implements.
+	let mut system_module = system.type_.path.clone();
+	system_module.segments.pop();
+	system_module.segments.pop_punct();
 
 	// This is a vector of all the pallet names, not including system.
-	let pallet_names = pallets.iter().map(|(name, _)| name.clone()).collect::<Vec<_>>();
+	let pallet_names = pallets.iter().map(|pallet| pallet.name.clone()).collect::<Vec<_>>();
 	// This is a vector of all the pallet types, not including system.
-	let pallet_types = pallets.iter().map(|(_, type_)| type_.clone()).collect::<Vec<_>>();
+	let pallet_types = pallets.iter().map(|pallet| pallet.type_.clone()).collect::<Vec<_>>();
+	// The pallets whose events are part of `RuntimeEvent`, which are those not marked
+	// `#[no_event]`, with their names and the `Event` type of the instance the runtime includes.
+	let event_pallets = pallets.iter().filter(|pallet| pallet.has_event).collect::<Vec<_>>();
+	let event_names = event_pallets.iter().map(|pallet| pallet.name.clone()).collect::<Vec<_>>();
+	let pallet_events = event_pallets
+		.iter()
+		.map(|pallet| pallet_item(&pallet.type_, "Event"))
+		.collect::<Vec<_>>();
+
+	// The pallets whose calls are part of `RuntimeCall`, including system unless it is marked
+	// `#[no_call]`, with their names, types and `Call` types.
+	let callable = std::iter::once(&system)
+		.chain(&pallets)
+		.enumerate()
+		.filter(|(_, pallet)| pallet.callable)
+		.collect::<Vec<_>>();
+	// The index of each of these pallets, written first in the encoding of its calls. It is the
+	// position of the pallet in the runtime struct, so it does not change when another pallet has
+	// no calls.
+	let pallet_index = callable.iter().map(|(index, _)| *index as u8).collect::<Vec<_>>();
+	let call_names = callable.iter().map(|(_, pallet)| pallet.name.clone()).collect::<Vec<_>>();
+	let call_types = callable.iter().map(|(_, pallet)| pallet.type_.clone()).collect::<Vec<_>>();
+	let call_items = call_types.iter().map(|type_| pallet_item(type_, "Call")).collect::<Vec<_>>();
+	let system = system.type_;
 
 	// This quote block implements functions on the `Runtime` struct.
 	let runtime_impl = quote! {
@@ -17,33 +48,119 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 			fn new() -> Self {
 				Self {
 					// Since system is not included in the list of pallets, we manually add it here.
-					system: <system::Pallet::<Self>>::new(),
+					system: <#system>::new(),
 					#(
 						#pallet_names: <#pallet_types>::new()
 					),*
 				}
 			}
 
-			// Execute a block of extrinsics. Increments the block number.
-			fn execute_block(&mut self, block: types::Block) -> crate::support::DispatchResult {
-				self.system.inc_block_number();
-				if block.header.block_number != self.system.block_number() {
-					return Err(&"block number does not match what is expected")
-				}
-				for (i, support::Extrinsic { caller, call }) in block.extrinsics.into_iter().enumerate() {
-					self.system.inc_nonce(&caller);
-					let _res = self.dispatch(caller, call).map_err(|e| {
-						eprintln!(
-							"Extrinsic Error\n\tBlock Number: {}\n\tExtrinsic Number: {}\n\tError: {}",
-							block.header.block_number, i, e
-						)
-					});
-				}
-				Ok(())
+			// Execute a block of extrinsics. Increments the block number and clears the events and
+			// weight of the previous block.
+			//
+			// A block is valid or invalid as a whole. An invalid block leaves no storage changes
+			// behind, so a valid block with the same number can be executed next.
+			fn execute_block(&mut self, block: #block) -> crate::support::DispatchResult {
+				crate::support::storage::transactional(|| {
+					<#system>::inc_block_number();
+					<#system>::reset_events();
+					<#system>::reset_block_weight();
+					if block.header.block_number != <#system>::block_number() {
+						return Err("block number does not match what is expected")
+					}
+					// The hooks of every pallet are called in declaration order.
+					#(
+						let weight = <#pallet_types as crate::support::traits::Hooks<
+							<Self as #system_module::Config>::BlockNumber,
+						>>::on_initialize(block.header.block_number);
+						<#system>::register_extra_weight(weight);
+					)*
+					// Inherents come first. A block with an invalid inherent is invalid as a whole.
+					for (i, call) in block.inherents.into_iter().enumerate() {
+						<#system>::register_extra_weight(
+							<<Self as #system_module::Config>::ExtrinsicBaseWeight as crate::support::traits::Get<_>>::get(),
+						);
+						crate::support::DispatchInherent::dispatch_inherent(self, call).map_err(|e| {
+							eprintln!(
+								"Inherent Error\n\tBlock Number: {}\n\tInherent Number: {}\n\tError: {}",
+								block.header.block_number, i, e
+							);
+							e
+						})?;
+					}
+					// So is a block missing an inherent that a pallet requires.
+					#(
+						<#pallet_types as crate::support::traits::Hooks<
+							<Self as #system_module::Config>::BlockNumber,
+						>>::check_inherents(block.header.block_number).map_err(|e| {
+							eprintln!(
+								"Inherent Error\n\tBlock Number: {}\n\tError: {}",
+								block.header.block_number, e
+							);
+							e
+						})?;
+					)*
+					for (i, #extrinsic { caller, call }) in block.extrinsics.into_iter().enumerate() {
+						<#system>::inc_nonce(&caller);
+						<#system>::register_extra_weight(
+							<<Self as #system_module::Config>::ExtrinsicBaseWeight as crate::support::traits::Get<_>>::get(),
+						);
+						// Each extrinsic runs in its own transactional layer, so a failing extrinsic
+						// leaves no storage changes or events behind. The nonce increment is kept.
+						let _res = crate::support::storage::transactional(|| {
+							// Only the runtime itself dispatches calls as root.
+							if caller == <<Self as #system_module::Config>::RootAccount as crate::support::traits::Get<_>>::get() {
+								return Err("Extrinsics cannot be signed by the root account");
+							}
+							self.dispatch(caller, call)
+						}).map_err(|e| {
+							eprintln!(
+								"Extrinsic Error\n\tBlock Number: {}\n\tExtrinsic Number: {}\n\tError: {}",
+								block.header.block_number, i, e
+							)
+						});
+					}
+					// Each pallet gets the weight left after the previous ones used theirs.
+					#(
+						let weight = <#pallet_types as crate::support::traits::Hooks<
+							<Self as #system_module::Config>::BlockNumber,
+						>>::on_idle(
+							block.header.block_number,
+							<#system>::remaining_block_weight(),
+						);
+						<#system>::register_extra_weight(weight);
+					)*
+					#(
+						<#pallet_types as crate::support::traits::Hooks<
+							<Self as #system_module::Config>::BlockNumber,
+						>>::on_finalize(block.header.block_number);
+					)*
+					Ok(())
+				})
 			}
 		}
 	};
 
+	// This quote block implements the `RuntimeEvent` enum, and the conversion from each pallet's
+	// events into it.
+	let event_impl = quote! {
+		// These are all the events which can be deposited in the system pallet.
+		// Note that it is just an accumulation of the events of each pallet.
+		#[allow(non_camel_case_types)]
+		#[derive(Clone, Debug, PartialEq)]
+		pub enum RuntimeEvent {
+			#( #event_names(#pallet_events) ),*
+		}
+
+		#(
+			impl From<#pallet_events> for RuntimeEvent {
+				fn from(event: #pallet_events) -> Self {
+					RuntimeEvent::#event_names(event)
+				}
+			}
+		)*
+	};
+
 	// This quote block implements the `RuntimeCall` enum and implements the `Dispatch` trait.
 	let dispatch_impl = quote! {
 		// These are all the calls which are exposed to the world.
@@ -51,12 +168,13 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 		//
 		// The parsed function names will be `snake_case`, and that will show up in the enum.
 		#[allow(non_camel_case_types)]
+		#[derive(Clone, Debug, PartialEq, Eq)]
 		pub enum RuntimeCall {
-			#( #pallet_names(#pallet_names::Call<#runtime_struct>) ),*
+			#( #call_names(#call_items) ),*
 		}
 
 		impl crate::support::Dispatch for #runtime_struct {
-			type Caller = <Runtime as system::Config>::AccountId;
+			type Caller = <#runtime_struct as #system_module::Config>::AccountId;
 			type Call = RuntimeCall;
 			// Dispatch a call on behalf of a caller. Increments the caller's nonce.
 			//
@@ -72,20 +190,113 @@ pub fn expand_runtime(def: RuntimeDef) -> proc_macro2::TokenStream {
 				// to the appropriate pallet level call.
 				match runtime_call {
 					#(
-						RuntimeCall::#pallet_names(call) => {
-							self.#pallet_names.dispatch(caller, call)?;
+						RuntimeCall::#call_names(call) => {
+							self.#call_names.dispatch(caller, call)?;
 						}
 					),*
 				}
 				Ok(())
 			}
 		}
+
+		// A call is encoded as the index of its pallet, followed by the encoding of the pallet call.
+		impl crate::support::codec::Encode for RuntimeCall {
+			fn encode_to(&self, dest: &mut Vec<u8>) {
+				match self {
+					#(
+						RuntimeCall::#call_names(call) => {
+							dest.push(#pallet_index);
+							crate::support::codec::Encode::encode_to(call, dest);
+						}
+					),*
+				}
+			}
+		}
+
+		impl crate::support::DispatchInherent for #runtime_struct {
+			type Call = RuntimeCall;
+
+			fn dispatch_inherent(&mut self, runtime_call: Self::Call) -> crate::support::DispatchResult {
+				match runtime_call {
+					#(
+						RuntimeCall::#call_names(call) => {
+							crate::support::DispatchInherent::dispatch_inherent(&mut self.#call_names, call)?;
+						}
+					),*
+				}
+				Ok(())
+			}
+		}
+
+		// Pallets keep their state in the shared storage, so a call can be dispatched to a fresh
+		// instance of its pallet. This lets pallets dispatch calls of other pallets.
+		impl crate::support::Dispatchable for RuntimeCall {
+			type Caller = <#runtime_struct as #system_module::Config>::AccountId;
+
+			fn dispatch(self, caller: Self::Caller) -> crate::support::DispatchResult {
+				match self {
+					#(
+						RuntimeCall::#call_names(call) => {
+							crate::support::Dispatch::dispatch(&mut <#call_types>::new(), caller, call)
+						}
+					),*
+				}
+			}
+		}
+	};
+
+	// This checks that the type of every field is a pallet, so that a field which is not gets an
+	// error pointing at its type, rather than errors about the code generated for it. Only the
+	// pallets in `RuntimeCall` need to be callable.
+	let pallet_checks = pallets.iter().map(|pallet| {
+		let type_ = &pallet.type_;
+		let check = if pallet.callable {
+			quote! { assert_callable }
+		} else {
+			quote! { assert_pallet }
+		};
+		quote_spanned! { type_.span() =>
+			#check::<#type_>();
+		}
+	});
+	let check_impl = quote! {
+		#[allow(dead_code)]
+		const _: () = {
+			fn assert_pallet<P>()
+			where
+				P: crate::support::traits::Hooks<<#runtime_struct as #system_module::Config>::BlockNumber>,
+			{
+			}
+
+			fn assert_callable<P>()
+			where
+				P: crate::support::Dispatch
+					+ crate::support::traits::Hooks<<#runtime_struct as #system_module::Config>::BlockNumber>,
+			{
+			}
+
+			fn assert_pallets() {
+				#( #pallet_checks )*
+			}
+		};
 	};
 
 	// We combine and return all the generated code.
 	quote! {
+		#check_impl
 		#dispatch_impl
+		#event_impl
 		#runtime_impl
 	}
 	.into()
-}
\ No newline at end of file
+}
+
+/// The item `name` of the module of a pallet, with the same generics as the pallet. For example
+/// the `Call` of `balances::Pallet<Runtime, Instance1>` is `balances::Call<Runtime, Instance1>`.
+fn pallet_item(pallet_type: &syn::TypePath, name: &str) -> syn::TypePath {
+	let mut item = pallet_type.clone();
+	if let Some(last) = item.path.segments.last_mut() {
+		last.ident = syn::Ident::new(name, last.ident.span());
+	}
+	item
+}
@@ -3,23 +3,30 @@ pub mod parse;
 
 /// See the `fn runtime` docs at the `lib.rs` of this crate for a high level definition.
 pub fn runtime(
-	_attr: proc_macro::TokenStream,
+	attr: proc_macro::TokenStream,
 	item: proc_macro::TokenStream,
 ) -> proc_macro::TokenStream {
-	// The final expanded code will be placed here.
-	// Since our macro only adds new code, our final product will contain all of our old code too,
-	// hence we clone `item`.
-	let mut finished = item.clone();
-	let item_mod = syn::parse_macro_input!(item as syn::Item);
+	let mut item_mod = syn::parse_macro_input!(item as syn::Item);
+	let attr = syn::parse_macro_input!(attr as parse::RuntimeAttr);
 
 	// First we parse the `Runtime` struct...
-	let generated: proc_macro::TokenStream = match parse::RuntimeDef::try_from(item_mod.clone()) {
-		// ..then we generate our new code.
-		Ok(def) => expand::expand_runtime(def).into(),
-		Err(e) => e.to_compile_error().into(),
-	};
+	let generated: proc_macro::TokenStream =
+		match parse::RuntimeDef::try_from(attr, item_mod.clone()) {
+			// ..then we generate our new code.
+			Ok(def) => expand::expand_runtime(def).into(),
+			Err(e) => e.to_compile_error().into(),
+		};
+
+	// Our final product contains all of our old code too, without the `#[no_call]` and
+	// `#[no_event]` attributes which only have a meaning for this macro.
+	if let syn::Item::Struct(item_struct) = &mut item_mod {
+		for field in item_struct.fields.iter_mut() {
+			field.attrs.retain(|attr| !parse::is_field_attr(attr));
+		}
+	}
+	let mut finished: proc_macro::TokenStream = quote::quote!(#item_mod).into();
 
 	// Add our generated code to the end, and return the final result.
 	finished.extend(generated);
 	return finished;
-}
\ No newline at end of file
+}
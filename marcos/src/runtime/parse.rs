@@ -7,11 +7,71 @@ pub struct RuntimeDef {
 	pub runtime_struct: syn::Ident,
 	/// This is the list of pallets included in the `Runtime` struct. We omit `system` from this
 	/// list, but during parsing we check that system exists.
-	pub pallets: Vec<(syn::Ident, syn::Type)>,
+	pub pallets: Vec<PalletDef>,
+	/// This is the `system` field. The module of the system pallet is found from its type.
+	pub system: PalletDef,
+	/// The paths given to the attribute of the macro. See `RuntimeAttr`.
+	pub attr: RuntimeAttr,
+}
+
+/// This is what we keep about each pallet field of the `Runtime` struct.
+#[derive(Debug)]
+pub struct PalletDef {
+	/// The name of the field.
+	pub name: syn::Ident,
+	/// The path of the type of the pallet, like `balances::Pallet<Runtime>`.
+	pub type_: syn::TypePath,
+	/// Whether the calls of the pallet are part of `RuntimeCall`. A field marked `#[no_call]` is
+	/// not, so its pallet does not need a `Call`.
+	pub callable: bool,
+	/// Whether the events of the pallet are part of `RuntimeEvent`. A field marked `#[no_event]`
+	/// is not, so its pallet does not need an `Event`.
+	pub has_event: bool,
+}
+
+/// Whether `attr` is one of the field attributes this macro understands, `#[no_call]` and
+/// `#[no_event]`.
+pub fn is_field_attr(attr: &syn::Attribute) -> bool {
+	attr.path().is_ident("no_call") || attr.path().is_ident("no_event")
+}
+
+/// The paths the runtime is configured with, as in `#[runtime(block = types::Block)]`.
+#[derive(Debug)]
+pub struct RuntimeAttr {
+	/// The type of the blocks `execute_block` takes. It defaults to `types::Block`.
+	pub block: syn::Path,
+	/// The struct the extrinsics of a block are made of, with `caller` and `call` fields. It
+	/// defaults to `support::Extrinsic`.
+	pub extrinsic: syn::Path,
+}
+
+impl syn::parse::Parse for RuntimeAttr {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let mut attr = Self {
+			block: syn::parse_quote!(types::Block),
+			extrinsic: syn::parse_quote!(support::Extrinsic),
+		};
+		let args =
+			syn::punctuated::Punctuated::<syn::MetaNameValue, syn::Token![,]>::parse_terminated(input)?;
+		for arg in args {
+			let syn::Expr::Path(syn::ExprPath { path, .. }) = &arg.value else {
+				return Err(syn::Error::new(arg.value.span(), "expected a path"))
+			};
+			if arg.path.is_ident("block") {
+				attr.block = path.clone();
+			} else if arg.path.is_ident("extrinsic") {
+				attr.extrinsic = path.clone();
+			} else {
+				let msg = "unknown runtime argument, expected `block` or `extrinsic`";
+				return Err(syn::Error::new(arg.path.span(), msg))
+			}
+		}
+		Ok(attr)
+	}
 }
 
 impl RuntimeDef {
-	pub fn try_from(item: syn::Item) -> syn::Result<Self> {
+	pub fn try_from(attr: RuntimeAttr, item: syn::Item) -> syn::Result<Self> {
 		// First we check that we are parsing a `struct`.
 		let item_struct = if let syn::Item::Struct(item) = item {
 			item
@@ -21,26 +81,50 @@ impl RuntimeDef {
 
 		// We check that the `Runtime` includes the `system` pallet as the first item.
 		check_system(&item_struct)?;
+		let mut fields = item_struct.fields.into_iter().map(|field| pallet_def(&field));
+		let system = fields.next().expect("checked by check_system")?;
 
 		let runtime_struct = item_struct.ident;
 
-		// Here is where we will store a list of all the pallets.
-		let mut pallets = vec![];
-		// We skip `system`, which we ensure is the first field in `check_system`.
-		for field in item_struct.fields.into_iter().skip(1) {
-			if let Some(ident) = field.ident {
-				pallets.push((ident, field.ty))
-			}
-		}
+		// Here is where we will store a list of all the pallets, which are the fields left after
+		// `system`.
+		let pallets = fields.collect::<syn::Result<Vec<_>>>()?;
 
-		Ok(Self { runtime_struct, pallets })
+		Ok(Self { runtime_struct, pallets, system, attr })
 	}
 }
 
+/// Parse a pallet field. Its type must be a path to a `Pallet` struct in the module of the pallet,
+/// like `balances::Pallet<Runtime>`. We find the `Call` and `Event` of the pallet next to it.
+fn pallet_def(field: &syn::Field) -> syn::Result<PalletDef> {
+	let Some(name) = field.ident.clone() else {
+		return Err(syn::Error::new(field.span(), "runtime fields are expected to be named"))
+	};
+	let type_ = match &field.ty {
+		syn::Type::Path(type_path)
+			if type_path.qself.is_none() &&
+				type_path.path.segments.len() > 1 &&
+				type_path.path.segments.last().is_some_and(|last| last.ident == "Pallet") =>
+			type_path.clone(),
+		ty => {
+			let msg = format!(
+				"the field `{}` is not a pallet, expected a type like `balances::Pallet<Runtime>`",
+				name
+			);
+			return Err(syn::Error::new(ty.span(), msg))
+		},
+	};
+	let callable = !field.attrs.iter().any(|attr| attr.path().is_ident("no_call"));
+	let has_event = !field.attrs.iter().any(|attr| attr.path().is_ident("no_event"));
+	Ok(PalletDef { name, type_, callable, has_event })
+}
+
 /// This function checks that the `system` pallet is the first pallet included in the `Runtime`
-/// struct. We make many assumptions about the `system` pallet in order to keep these macros simple.
-/// For example, we assume that the system pallet has no callable functions, and that it contains
-/// specific functions like incrementing the block number and a user's nonce.
+/// struct, in a field named `system`. Its type can be any path to a `Pallet`, and the macros find
+/// the `Config` of the system pallet in the module of that path. We still make many assumptions
+/// about the `system` pallet in order to keep these macros simple. For example, we assume that
+/// the system pallet has no callable functions, and that it contains specific functions like
+/// incrementing the block number and a user's nonce.
 ///
 /// You can consider these macros to be tightly coupled to the logic of the `system` pallet.
 fn check_system(item_struct: &syn::ItemStruct) -> syn::Result<()> {
@@ -64,4 +148,67 @@ fn check_system(item_struct: &syn::ItemStruct) -> syn::Result<()> {
 	}
 
 	Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn runtime_def(item: syn::Item) -> syn::Result<RuntimeDef> {
+		RuntimeDef::try_from(syn::parse_quote!(), item)
+	}
+
+	#[test]
+	fn pallets_are_found_by_their_type() {
+		let def = runtime_def(syn::parse_quote! {
+			pub struct Runtime {
+				system: frame::system::Pallet<Runtime>,
+				poe: proof_of_existence::Pallet<Runtime>,
+				#[no_call]
+				#[no_event]
+				randomness: randomness::Pallet<Runtime>,
+			}
+		})
+		.unwrap();
+
+		assert_eq!(def.system.type_, syn::parse_quote!(frame::system::Pallet<Runtime>));
+		assert!(def.system.callable);
+		assert_eq!(def.pallets.len(), 2);
+		let poe = &def.pallets[0];
+		assert_eq!(poe.name, "poe");
+		assert_eq!(poe.type_, syn::parse_quote!(proof_of_existence::Pallet<Runtime>));
+		assert!(poe.callable);
+		assert!(poe.has_event);
+		assert!(!def.pallets[1].callable);
+		assert!(!def.pallets[1].has_event);
+	}
+
+	#[test]
+	fn fields_must_be_pallets() {
+		let err = runtime_def(syn::parse_quote! {
+			pub struct Runtime {
+				system: system::Pallet<Runtime>,
+				balances: u32,
+			}
+		})
+		.unwrap_err();
+
+		assert_eq!(
+			err.to_string(),
+			"the field `balances` is not a pallet, expected a type like `balances::Pallet<Runtime>`"
+		);
+	}
+
+	#[test]
+	fn system_comes_first() {
+		let err = runtime_def(syn::parse_quote! {
+			pub struct Runtime {
+				balances: balances::Pallet<Runtime>,
+				system: system::Pallet<Runtime>,
+			}
+		})
+		.unwrap_err();
+
+		assert_eq!(err.to_string(), "first field is expected to be named system");
+	}
+}
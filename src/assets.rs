@@ -0,0 +1,522 @@
+use std::{fmt::Debug, marker::PhantomData};
+
+use num::traits::{CheckedAdd, CheckedSub, Zero};
+
+use crate::{
+    balances,
+    support::{
+        codec::Encode,
+        storage::StorageMap,
+        traits::{Get, Hooks},
+        DispatchResult,
+    },
+    system,
+};
+
+/// Assets are accounted in the `Balance` of the balances pallet, with the same checked arithmetic.
+pub trait Config: balances::Config + system::Config<RuntimeEvent: From<Event<Self>>> {
+    /// The identifier of an asset, chosen by its creator.
+    type AssetId: Copy + Ord + Debug + Encode;
+    /// The longest name or symbol an asset can have, in bytes.
+    type StringLimit: Get<u32>;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssetDetails<AccountId, Balance> {
+    /// The account that created the asset, which can change its admin and metadata.
+    pub owner: AccountId,
+    /// The account that can mint, burn and freeze the asset.
+    pub admin: AccountId,
+    /// The total amount of the asset held by all accounts.
+    pub supply: Balance,
+    /// Whether transfers of the asset are suspended.
+    pub is_frozen: bool,
+}
+
+pub type AssetDetailsOf<T> =
+    AssetDetails<<T as system::Config>::AccountId, <T as balances::Config>::Balance>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssetAccount<Balance> {
+    pub balance: Balance,
+    /// Whether transfers from the account are suspended.
+    pub is_frozen: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AssetMetadata {
+    pub name: Vec<u8>,
+    pub symbol: Vec<u8>,
+    /// The number of decimals of the amounts the asset is displayed with.
+    pub decimals: u8,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event<T: Config> {
+    Created {
+        asset_id: T::AssetId,
+        creator: T::AccountId,
+        admin: T::AccountId,
+    },
+    Issued {
+        asset_id: T::AssetId,
+        owner: T::AccountId,
+        amount: T::Balance,
+    },
+    Burned {
+        asset_id: T::AssetId,
+        owner: T::AccountId,
+        amount: T::Balance,
+    },
+    Transferred {
+        asset_id: T::AssetId,
+        from: T::AccountId,
+        to: T::AccountId,
+        amount: T::Balance,
+    },
+    /// Transfers from the account were suspended.
+    Frozen {
+        asset_id: T::AssetId,
+        who: T::AccountId,
+    },
+    Thawed {
+        asset_id: T::AssetId,
+        who: T::AccountId,
+    },
+    /// Every transfer of the asset was suspended.
+    AssetFrozen {
+        asset_id: T::AssetId,
+    },
+    AssetThawed {
+        asset_id: T::AssetId,
+    },
+    AdminChanged {
+        asset_id: T::AssetId,
+        admin: T::AccountId,
+    },
+    MetadataSet {
+        asset_id: T::AssetId,
+        name: Vec<u8>,
+        symbol: Vec<u8>,
+        decimals: u8,
+    },
+    MetadataCleared {
+        asset_id: T::AssetId,
+    },
+}
+
+/// The details of each asset.
+pub struct Asset<T>(PhantomData<T>);
+impl<T: Config> StorageMap for Asset<T> {
+    type Key = T::AssetId;
+    type Value = AssetDetailsOf<T>;
+}
+
+/// The holding of each account in each asset.
+pub struct Account<T>(PhantomData<T>);
+impl<T: Config> StorageMap for Account<T> {
+    type Key = (T::AssetId, T::AccountId);
+    type Value = AssetAccount<T::Balance>;
+}
+
+/// The name, symbol and decimals of each asset that has them.
+pub struct Metadata<T>(PhantomData<T>);
+impl<T: Config> StorageMap for Metadata<T> {
+    type Key = T::AssetId;
+    type Value = AssetMetadata;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pallet<T: Config>(PhantomData<T>);
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+    /// Create the asset `asset_id`, owned by the caller and administered by `admin`.
+    pub fn create(
+        &mut self,
+        caller: T::AccountId,
+        asset_id: T::AssetId,
+        admin: T::AccountId,
+    ) -> DispatchResult {
+        if Asset::<T>::contains_key(&asset_id) {
+            return Err("Asset already exists");
+        }
+
+        Asset::<T>::insert(
+            asset_id,
+            AssetDetails {
+                owner: caller.clone(),
+                admin: admin.clone(),
+                supply: T::Balance::zero(),
+                is_frozen: false,
+            },
+        );
+        system::Pallet::<T>::deposit_event(Event::Created {
+            asset_id,
+            creator: caller,
+            admin,
+        });
+        Ok(())
+    }
+
+    /// Create `amount` of the asset in the account of `beneficiary`. Only the admin can mint.
+    pub fn mint(
+        &mut self,
+        caller: T::AccountId,
+        asset_id: T::AssetId,
+        beneficiary: T::AccountId,
+        amount: T::Balance,
+    ) -> DispatchResult {
+        let mut details = Self::ensure_admin(&caller, asset_id)?;
+        details.supply = details
+            .supply
+            .checked_add(&amount)
+            .ok_or("Overflow when adding to supply")?;
+        let mut account = Self::account(asset_id, &beneficiary);
+        account.balance = account
+            .balance
+            .checked_add(&amount)
+            .ok_or("Overflow when adding to balance")?;
+
+        Asset::<T>::insert(asset_id, details);
+        Account::<T>::insert((asset_id, beneficiary.clone()), account);
+        system::Pallet::<T>::deposit_event(Event::Issued {
+            asset_id,
+            owner: beneficiary,
+            amount,
+        });
+        Ok(())
+    }
+
+    /// Destroy `amount` of the asset held by `who`. Only the admin can burn.
+    pub fn burn(
+        &mut self,
+        caller: T::AccountId,
+        asset_id: T::AssetId,
+        who: T::AccountId,
+        amount: T::Balance,
+    ) -> DispatchResult {
+        let mut details = Self::ensure_admin(&caller, asset_id)?;
+        let mut account = Self::account(asset_id, &who);
+        account.balance = account
+            .balance
+            .checked_sub(&amount)
+            .ok_or("Insufficient asset balance")?;
+        details.supply = details
+            .supply
+            .checked_sub(&amount)
+            .ok_or("Underflow when subtracting from supply")?;
+
+        Asset::<T>::insert(asset_id, details);
+        Self::set_account(asset_id, &who, account);
+        system::Pallet::<T>::deposit_event(Event::Burned {
+            asset_id,
+            owner: who,
+            amount,
+        });
+        Ok(())
+    }
+
+    pub fn transfer(
+        &mut self,
+        caller: T::AccountId,
+        asset_id: T::AssetId,
+        to: T::AccountId,
+        amount: T::Balance,
+    ) -> DispatchResult {
+        let details = Self::asset(asset_id)?;
+        let mut from_account = Self::account(asset_id, &caller);
+        if details.is_frozen || from_account.is_frozen {
+            return Err("Asset is frozen");
+        }
+        from_account.balance = from_account
+            .balance
+            .checked_sub(&amount)
+            .ok_or("Insufficient asset balance")?;
+        Self::set_account(asset_id, &caller, from_account);
+
+        let mut to_account = Self::account(asset_id, &to);
+        to_account.balance = to_account
+            .balance
+            .checked_add(&amount)
+            .ok_or("Overflow when adding to balance")?;
+        Self::set_account(asset_id, &to, to_account);
+
+        system::Pallet::<T>::deposit_event(Event::Transferred {
+            asset_id,
+            from: caller,
+            to,
+            amount,
+        });
+        Ok(())
+    }
+
+    /// Suspend transfers of the asset from `who`. Only the admin can freeze.
+    pub fn freeze(
+        &mut self,
+        caller: T::AccountId,
+        asset_id: T::AssetId,
+        who: T::AccountId,
+    ) -> DispatchResult {
+        Self::ensure_admin(&caller, asset_id)?;
+        let mut account = Self::account(asset_id, &who);
+        account.is_frozen = true;
+        Account::<T>::insert((asset_id, who.clone()), account);
+        system::Pallet::<T>::deposit_event(Event::Frozen { asset_id, who });
+        Ok(())
+    }
+
+    pub fn thaw(
+        &mut self,
+        caller: T::AccountId,
+        asset_id: T::AssetId,
+        who: T::AccountId,
+    ) -> DispatchResult {
+        Self::ensure_admin(&caller, asset_id)?;
+        let mut account = Self::account(asset_id, &who);
+        account.is_frozen = false;
+        Self::set_account(asset_id, &who, account);
+        system::Pallet::<T>::deposit_event(Event::Thawed { asset_id, who });
+        Ok(())
+    }
+
+    /// Suspend every transfer of the asset. Only the admin can freeze.
+    pub fn freeze_asset(&mut self, caller: T::AccountId, asset_id: T::AssetId) -> DispatchResult {
+        let mut details = Self::ensure_admin(&caller, asset_id)?;
+        details.is_frozen = true;
+        Asset::<T>::insert(asset_id, details);
+        system::Pallet::<T>::deposit_event(Event::AssetFrozen { asset_id });
+        Ok(())
+    }
+
+    pub fn thaw_asset(&mut self, caller: T::AccountId, asset_id: T::AssetId) -> DispatchResult {
+        let mut details = Self::ensure_admin(&caller, asset_id)?;
+        details.is_frozen = false;
+        Asset::<T>::insert(asset_id, details);
+        system::Pallet::<T>::deposit_event(Event::AssetThawed { asset_id });
+        Ok(())
+    }
+
+    /// Hand the administration of the asset over to `admin`. Only the owner can change it.
+    pub fn set_admin(
+        &mut self,
+        caller: T::AccountId,
+        asset_id: T::AssetId,
+        admin: T::AccountId,
+    ) -> DispatchResult {
+        let mut details = Self::ensure_owner(&caller, asset_id)?;
+        details.admin = admin.clone();
+        Asset::<T>::insert(asset_id, details);
+        system::Pallet::<T>::deposit_event(Event::AdminChanged { asset_id, admin });
+        Ok(())
+    }
+
+    /// Set the name, symbol and decimals of the asset. Only the owner can set them.
+    pub fn set_metadata(
+        &mut self,
+        caller: T::AccountId,
+        asset_id: T::AssetId,
+        name: Vec<u8>,
+        symbol: Vec<u8>,
+        decimals: u8,
+    ) -> DispatchResult {
+        Self::ensure_owner(&caller, asset_id)?;
+        let limit = T::StringLimit::get() as usize;
+        if name.len() > limit || symbol.len() > limit {
+            return Err("Name or symbol is too long");
+        }
+
+        Metadata::<T>::insert(
+            asset_id,
+            AssetMetadata {
+                name: name.clone(),
+                symbol: symbol.clone(),
+                decimals,
+            },
+        );
+        system::Pallet::<T>::deposit_event(Event::MetadataSet {
+            asset_id,
+            name,
+            symbol,
+            decimals,
+        });
+        Ok(())
+    }
+
+    pub fn clear_metadata(&mut self, caller: T::AccountId, asset_id: T::AssetId) -> DispatchResult {
+        Self::ensure_owner(&caller, asset_id)?;
+        Metadata::<T>::remove(&asset_id).ok_or("Asset has no metadata")?;
+        system::Pallet::<T>::deposit_event(Event::MetadataCleared { asset_id });
+        Ok(())
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn new() -> Self {
+        Pallet(PhantomData)
+    }
+
+    pub fn asset(asset_id: T::AssetId) -> Result<AssetDetailsOf<T>, &'static str> {
+        Asset::<T>::get(&asset_id).ok_or("Asset not found")
+    }
+
+    /// The amount of the asset held by `who`.
+    pub fn balance(asset_id: T::AssetId, who: &T::AccountId) -> T::Balance {
+        Self::account(asset_id, who).balance
+    }
+
+    pub fn total_supply(asset_id: T::AssetId) -> T::Balance {
+        Asset::<T>::get(&asset_id)
+            .map(|details| details.supply)
+            .unwrap_or_else(T::Balance::zero)
+    }
+
+    pub fn metadata(asset_id: T::AssetId) -> Option<AssetMetadata> {
+        Metadata::<T>::get(&asset_id)
+    }
+
+    fn account(asset_id: T::AssetId, who: &T::AccountId) -> AssetAccount<T::Balance> {
+        Account::<T>::get(&(asset_id, who.clone())).unwrap_or(AssetAccount {
+            balance: T::Balance::zero(),
+            is_frozen: false,
+        })
+    }
+
+    /// Store the holding of `who`, or remove it once it is empty and not frozen.
+    fn set_account(asset_id: T::AssetId, who: &T::AccountId, account: AssetAccount<T::Balance>) {
+        if account.balance.is_zero() && !account.is_frozen {
+            Account::<T>::remove(&(asset_id, who.clone()));
+        } else {
+            Account::<T>::insert((asset_id, who.clone()), account);
+        }
+    }
+
+    fn ensure_owner(
+        who: &T::AccountId,
+        asset_id: T::AssetId,
+    ) -> Result<AssetDetailsOf<T>, &'static str> {
+        let details = Self::asset(asset_id)?;
+        if &details.owner != who {
+            return Err("Caller is not the owner of the asset");
+        }
+        Ok(details)
+    }
+
+    fn ensure_admin(
+        who: &T::AccountId,
+        asset_id: T::AssetId,
+    ) -> Result<AssetDetailsOf<T>, &'static str> {
+        let details = Self::asset(asset_id)?;
+        if &details.admin != who {
+            return Err("Caller is not the admin of the asset");
+        }
+        Ok(details)
+    }
+}
+
+impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {}
+
+#[cfg(test)]
+mod test {
+    use crate::{mock::TestConfig, support::traits::ConstU32, types};
+
+    use super::*;
+
+    impl Config for TestConfig {
+        type AssetId = types::AssetId;
+        type StringLimit = ConstU32<8>;
+    }
+
+    type Assets = Pallet<TestConfig>;
+
+    #[test]
+    fn admin_mints_and_burns() {
+        let (a, b) = ("A".to_string(), "B".to_string());
+        let mut assets = Assets::new();
+        assets.create(a.clone(), 0, b.clone()).unwrap();
+        assert_eq!(
+            assets.create(b.clone(), 0, b.clone()),
+            Err("Asset already exists")
+        );
+
+        assert_eq!(
+            assets.mint(a.clone(), 0, a.clone(), 100),
+            Err("Caller is not the admin of the asset")
+        );
+        assets.mint(b.clone(), 0, a.clone(), 100).unwrap();
+        assert_eq!(
+            assets.mint(b.clone(), 0, a.clone(), u128::MAX),
+            Err("Overflow when adding to supply")
+        );
+        assert_eq!(
+            assets.burn(b.clone(), 0, a.clone(), 101),
+            Err("Insufficient asset balance")
+        );
+        assets.burn(b.clone(), 0, a.clone(), 40).unwrap();
+        assert_eq!(Assets::balance(0, &a), 60);
+        assert_eq!(Assets::total_supply(0), 60);
+        assert_eq!(assets.mint(b, 1, a, 100), Err("Asset not found"));
+    }
+
+    #[test]
+    fn frozen_accounts_cannot_transfer() {
+        let (a, b) = ("A".to_string(), "B".to_string());
+        let mut assets = Assets::new();
+        assets.create(a.clone(), 0, a.clone()).unwrap();
+        assets.mint(a.clone(), 0, a.clone(), 100).unwrap();
+
+        assets.transfer(a.clone(), 0, b.clone(), 30).unwrap();
+        assert_eq!(
+            assets.transfer(b.clone(), 0, a.clone(), 31),
+            Err("Insufficient asset balance")
+        );
+
+        assets.freeze(a.clone(), 0, b.clone()).unwrap();
+        assert_eq!(
+            assets.transfer(b.clone(), 0, a.clone(), 10),
+            Err("Asset is frozen")
+        );
+        assets.thaw(a.clone(), 0, b.clone()).unwrap();
+        assets.freeze_asset(a.clone(), 0).unwrap();
+        assert_eq!(
+            assets.transfer(a.clone(), 0, b.clone(), 10),
+            Err("Asset is frozen")
+        );
+        assets.thaw_asset(a.clone(), 0).unwrap();
+        assets.transfer(b.clone(), 0, a.clone(), 30).unwrap();
+        assert_eq!(Assets::balance(0, &a), 100);
+        assert_eq!(Assets::balance(0, &b), 0);
+    }
+
+    #[test]
+    fn owner_manages_admin_and_metadata() {
+        let (a, b) = ("A".to_string(), "B".to_string());
+        let mut assets = Assets::new();
+        assets.create(a.clone(), 0, a.clone()).unwrap();
+
+        assert_eq!(
+            assets.set_metadata(
+                a.clone(),
+                0,
+                b"Token".to_vec(),
+                b"TOKEN-TOO-LONG".to_vec(),
+                12
+            ),
+            Err("Name or symbol is too long")
+        );
+        assets
+            .set_metadata(a.clone(), 0, b"Token".to_vec(), b"TKN".to_vec(), 12)
+            .unwrap();
+        assert_eq!(Assets::metadata(0).unwrap().decimals, 12);
+
+        assert_eq!(
+            assets.set_admin(b.clone(), 0, b.clone()),
+            Err("Caller is not the owner of the asset")
+        );
+        assets.set_admin(a.clone(), 0, b.clone()).unwrap();
+        assets.mint(b.clone(), 0, b, 10).unwrap();
+
+        assets.clear_metadata(a.clone(), 0).unwrap();
+        assert_eq!(assets.clear_metadata(a, 0), Err("Asset has no metadata"));
+    }
+}
@@ -1,123 +1,879 @@
-use std::{collections::BTreeMap, fmt::Debug};
+use std::{convert::Infallible, fmt::Debug, marker::PhantomData};
 
-use num::{CheckedAdd, CheckedSub, Zero};
+use num::{traits::Saturating, CheckedAdd, CheckedSub, Zero};
 
-use crate::system;
+use crate::{
+    support::{
+        storage::{StorageMap, StorageValue},
+        traits::{
+            BalanceStatus, Currency, Hooks, Imbalance, LockIdentifier, LockableCurrency,
+            NamedReservableCurrency, ReservableCurrency, ReserveIdentifier, WithdrawReasons,
+        },
+        DispatchResult,
+    },
+    system,
+};
 
-pub trait Config: system::Config {
-    type Balance: Zero + CheckedAdd + CheckedSub + Copy + Debug;
+/// The pallet is instantiable: a runtime can include several copies of it, each with its own
+/// `Config<I>` and storage, like a native token and a test token.
+pub trait Config<I: 'static = ()>: system::Config<RuntimeEvent: From<Event<Self, I>>> {
+    type Balance: Zero + CheckedAdd + CheckedSub + Saturating + Copy + Debug + Ord;
 }
 
+/// The balance of an account, split between what it can spend and what is set aside.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccountData<Balance> {
+    /// Funds that can be transferred, subject to any locks.
+    pub free: Balance,
+    /// Funds set aside by `reserve`, including all holds.
+    pub reserved: Balance,
+}
+
+impl<Balance: Zero> AccountData<Balance> {
+    fn empty() -> Self {
+        Self {
+            free: Balance::zero(),
+            reserved: Balance::zero(),
+        }
+    }
+}
+
+/// A lock on part of the free balance of an account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BalanceLock<Balance> {
+    pub id: LockIdentifier,
+    pub amount: Balance,
+    pub reasons: WithdrawReasons,
+}
+
+/// Funds that were added to an account and to the total issuance.
+#[derive(Debug)]
+pub struct PositiveImbalance<T: Config<I>, I: 'static = ()>(T::Balance, PhantomData<I>);
+
+/// Funds that were removed from an account and from the total issuance.
 #[derive(Debug)]
-pub struct Pallet<T: Config> {
-    balances: BTreeMap<T::AccountId, T::Balance>,
+pub struct NegativeImbalance<T: Config<I>, I: 'static = ()>(T::Balance, PhantomData<I>);
+
+impl<T: Config<I>, I: 'static> Imbalance<T::Balance> for PositiveImbalance<T, I> {
+    fn zero() -> Self {
+        Self(T::Balance::zero(), PhantomData)
+    }
+
+    fn peek(&self) -> T::Balance {
+        self.0
+    }
+
+    fn merge(self, other: Self) -> Self {
+        Self(self.0.saturating_add(other.0), PhantomData)
+    }
+}
+
+impl<T: Config<I>, I: 'static> Imbalance<T::Balance> for NegativeImbalance<T, I> {
+    fn zero() -> Self {
+        Self(T::Balance::zero(), PhantomData)
+    }
+
+    fn peek(&self) -> T::Balance {
+        self.0
+    }
+
+    fn merge(self, other: Self) -> Self {
+        Self(self.0.saturating_add(other.0), PhantomData)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event<T: Config<I>, I: 'static = ()> {
+    Transfer {
+        from: T::AccountId,
+        to: T::AccountId,
+        amount: T::Balance,
+    },
+    /// The free balance of an account was set by root.
+    BalanceSet { who: T::AccountId, free: T::Balance },
+    /// New funds were added to an account.
+    Deposit {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    /// Funds were removed from an account and from circulation.
+    Withdraw {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    Reserved {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    Unreserved {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    /// Reserved funds were removed from an account and from circulation.
+    Slashed {
+        who: T::AccountId,
+        amount: T::Balance,
+    },
+    /// Reserved funds were moved to another account.
+    ReserveRepatriated {
+        from: T::AccountId,
+        to: T::AccountId,
+        amount: T::Balance,
+        destination_status: BalanceStatus,
+    },
+    #[doc(hidden)]
+    __Ignore(PhantomData<I>, Infallible),
+}
+
+/// The sum of all balances.
+pub struct TotalIssuance<T, I = ()>(PhantomData<(T, I)>);
+impl<T: Config<I>, I: 'static> StorageValue for TotalIssuance<T, I> {
+    type Value = T::Balance;
 }
 
+/// The free and reserved balance of each account.
+pub struct Account<T, I = ()>(PhantomData<(T, I)>);
+impl<T: Config<I>, I: 'static> StorageMap for Account<T, I> {
+    type Key = T::AccountId;
+    type Value = AccountData<T::Balance>;
+}
+
+/// The locks on the free balance of each account.
+pub struct Locks<T, I = ()>(PhantomData<(T, I)>);
+impl<T: Config<I>, I: 'static> StorageMap for Locks<T, I> {
+    type Key = T::AccountId;
+    type Value = Vec<BalanceLock<T::Balance>>;
+}
+
+/// The named parts of the reserved balance of each account.
+pub struct Holds<T, I = ()>(PhantomData<(T, I)>);
+impl<T: Config<I>, I: 'static> StorageMap for Holds<T, I> {
+    type Key = (T::AccountId, ReserveIdentifier);
+    type Value = T::Balance;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pallet<T: Config<I>, I: 'static = ()>(PhantomData<(T, I)>);
+
 #[macros::call]
-impl<T: Config> Pallet<T> {
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
     pub fn transfer(
         &mut self,
         caller: T::AccountId,
         to: T::AccountId,
         amount: T::Balance,
     ) -> Result<(), &'static str> {
-        let from_amount = self.balance(&caller);
-        let to_amount = self.balance(&to);
+        Self::do_transfer(&caller, &to, amount)
+    }
+
+    /// Set the free balance of `who`. Only root can call it.
+    pub fn force_set_balance(
+        &mut self,
+        caller: T::AccountId,
+        who: T::AccountId,
+        amount: T::Balance,
+    ) -> DispatchResult {
+        system::Pallet::<T>::ensure_root(&caller)?;
+        Self::set_balance(&who, amount);
+        system::Pallet::<T>::deposit_event(Event::BalanceSet { who, free: amount });
+        Ok(())
+    }
+}
+
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+    pub fn new() -> Self {
+        Pallet(PhantomData)
+    }
+
+    /// Set the free balance of `account`, leaving its reserved balance untouched.
+    pub fn set_balance(account: &T::AccountId, amount: T::Balance) {
+        let mut data = Self::account(account);
+        TotalIssuance::<T, I>::put(
+            Self::total_issuance()
+                .saturating_sub(data.free)
+                .saturating_add(amount),
+        );
+        data.free = amount;
+        Account::<T, I>::insert(account.clone(), data);
+    }
+
+    /// The free balance of `account`.
+    pub fn balance(account: &T::AccountId) -> T::Balance {
+        Self::account(account).free
+    }
+
+    pub fn account(account: &T::AccountId) -> AccountData<T::Balance> {
+        Account::<T, I>::get(account).unwrap_or_else(AccountData::empty)
+    }
 
-        println!("from amount {:#?}", from_amount);
+    pub fn locks(account: &T::AccountId) -> Vec<BalanceLock<T::Balance>> {
+        Locks::<T, I>::get(account).unwrap_or_default()
+    }
+
+    /// The part of the free balance of `account` that cannot be withdrawn for `reason`.
+    pub fn frozen_balance(account: &T::AccountId, reason: WithdrawReasons) -> T::Balance {
+        Self::locks(account)
+            .into_iter()
+            .filter(|lock| lock.reasons.restricts(reason))
+            .map(|lock| lock.amount)
+            .max()
+            .unwrap_or_else(T::Balance::zero)
+    }
+
+    fn do_transfer(from: &T::AccountId, to: &T::AccountId, amount: T::Balance) -> DispatchResult {
+        let from_amount = Self::balance(from);
+        let to_amount = Self::balance(to);
 
         let new_from_amount = (from_amount)
             .checked_sub(&amount)
             .ok_or("Insufficient fund")?;
+        Self::ensure_can_withdraw(from, new_from_amount, WithdrawReasons::Transfer)?;
 
         let new_to_amount = (to_amount)
             .checked_add(&amount)
             .ok_or("Overflow when adding to balance")?;
 
-        self.set_balance(&caller, new_from_amount);
-        self.set_balance(&to, new_to_amount);
+        Self::set_free(from, new_from_amount);
+        Self::set_free(to, new_to_amount);
+
+        system::Pallet::<T>::deposit_event(Event::Transfer {
+            from: from.clone(),
+            to: to.clone(),
+            amount,
+        });
+        Ok(())
+    }
+
+    /// Set the free balance of `account` after funds moved between accounts, so without touching
+    /// the total issuance.
+    fn set_free(account: &T::AccountId, free: T::Balance) {
+        let mut data = Self::account(account);
+        data.free = free;
+        Account::<T, I>::insert(account.clone(), data);
+    }
 
+    /// Check that the free balance of `account` may drop to `new_free` for `reason`.
+    fn ensure_can_withdraw(
+        account: &T::AccountId,
+        new_free: T::Balance,
+        reason: WithdrawReasons,
+    ) -> DispatchResult {
+        if new_free < Self::frozen_balance(account, reason) {
+            return Err("Liquidity restrictions prevent withdrawal");
+        }
         Ok(())
     }
+
+    /// The part of the reserve of `account` that is not claimed by any hold.
+    fn unnamed_reserve(account: &T::AccountId) -> T::Balance {
+        let held = Holds::<T, I>::iter()
+            .into_iter()
+            .filter(|((who, _), _)| who == account)
+            .fold(T::Balance::zero(), |acc, (_, amount)| {
+                acc.saturating_add(amount)
+            });
+        Self::account(account).reserved.saturating_sub(held)
+    }
+
+    /// Move up to `value` from reserved back to free. Returns the amount actually moved.
+    fn do_unreserve(who: &T::AccountId, value: T::Balance) -> T::Balance {
+        let mut data = Self::account(who);
+        let actual = value.min(data.reserved);
+        let Some(free) = data.free.checked_add(&actual) else {
+            return T::Balance::zero();
+        };
+        data.free = free;
+        data.reserved = data.reserved - actual;
+        Account::<T, I>::insert(who.clone(), data);
+
+        if !actual.is_zero() {
+            system::Pallet::<T>::deposit_event(Event::Unreserved {
+                who: who.clone(),
+                amount: actual,
+            });
+        }
+        actual
+    }
 }
 
-impl<T: Config> Pallet<T> {
-    pub fn new() -> Self {
-        Pallet {
-            balances: BTreeMap::new(),
+impl<T: Config<I>, I: 'static> Hooks<T::BlockNumber> for Pallet<T, I> {}
+
+impl<T: Config<I>, I: 'static> Currency<T::AccountId> for Pallet<T, I> {
+    type Balance = T::Balance;
+    type PositiveImbalance = PositiveImbalance<T, I>;
+    type NegativeImbalance = NegativeImbalance<T, I>;
+
+    fn free_balance(who: &T::AccountId) -> Self::Balance {
+        Self::account(who).free
+    }
+
+    fn total_balance(who: &T::AccountId) -> Self::Balance {
+        let data = Self::account(who);
+        data.free.saturating_add(data.reserved)
+    }
+
+    fn total_issuance() -> Self::Balance {
+        TotalIssuance::<T, I>::get().unwrap_or_else(T::Balance::zero)
+    }
+
+    fn transfer(from: &T::AccountId, to: &T::AccountId, value: Self::Balance) -> DispatchResult {
+        Self::do_transfer(from, to, value)
+    }
+
+    fn withdraw(
+        who: &T::AccountId,
+        value: Self::Balance,
+        reasons: WithdrawReasons,
+    ) -> Result<Self::NegativeImbalance, &'static str> {
+        let mut data = Self::account(who);
+        data.free = data.free.checked_sub(&value).ok_or("Insufficient fund")?;
+        Self::ensure_can_withdraw(who, data.free, reasons)?;
+        Account::<T, I>::insert(who.clone(), data);
+        TotalIssuance::<T, I>::put(Self::total_issuance().saturating_sub(value));
+
+        system::Pallet::<T>::deposit_event(Event::Withdraw {
+            who: who.clone(),
+            amount: value,
+        });
+        Ok(NegativeImbalance(value, PhantomData))
+    }
+
+    fn deposit_creating(who: &T::AccountId, value: Self::Balance) -> Self::PositiveImbalance {
+        let mut data = Self::account(who);
+        let Some(free) = data.free.checked_add(&value) else {
+            return PositiveImbalance::zero();
+        };
+        data.free = free;
+        Account::<T, I>::insert(who.clone(), data);
+        TotalIssuance::<T, I>::put(Self::total_issuance().saturating_add(value));
+
+        system::Pallet::<T>::deposit_event(Event::Deposit {
+            who: who.clone(),
+            amount: value,
+        });
+        PositiveImbalance(value, PhantomData)
+    }
+
+    fn resolve_creating(who: &T::AccountId, imbalance: Self::NegativeImbalance) {
+        Self::deposit_creating(who, imbalance.peek());
+    }
+}
+
+impl<T: Config<I>, I: 'static> ReservableCurrency<T::AccountId> for Pallet<T, I> {
+    fn reserved_balance(who: &T::AccountId) -> Self::Balance {
+        Self::account(who).reserved
+    }
+
+    fn can_reserve(who: &T::AccountId, value: Self::Balance) -> bool {
+        let data = Self::account(who);
+        match data.free.checked_sub(&value) {
+            Some(new_free) => {
+                Self::ensure_can_withdraw(who, new_free, WithdrawReasons::Reserve).is_ok()
+            }
+            None => false,
+        }
+    }
+
+    fn reserve(who: &T::AccountId, value: Self::Balance) -> DispatchResult {
+        let mut data = Self::account(who);
+        data.free = data.free.checked_sub(&value).ok_or("Insufficient fund")?;
+        Self::ensure_can_withdraw(who, data.free, WithdrawReasons::Reserve)?;
+        data.reserved = data
+            .reserved
+            .checked_add(&value)
+            .ok_or("Overflow when adding to balance")?;
+        Account::<T, I>::insert(who.clone(), data);
+
+        system::Pallet::<T>::deposit_event(Event::Reserved {
+            who: who.clone(),
+            amount: value,
+        });
+        Ok(())
+    }
+
+    fn unreserve(who: &T::AccountId, value: Self::Balance) -> Self::Balance {
+        let value_to_unreserve = value.min(Self::unnamed_reserve(who));
+        let actual = Self::do_unreserve(who, value_to_unreserve);
+        value - actual
+    }
+
+    fn slash_reserved(
+        who: &T::AccountId,
+        value: Self::Balance,
+    ) -> (Self::NegativeImbalance, Self::Balance) {
+        let actual = value.min(Self::unnamed_reserve(who));
+        if actual.is_zero() {
+            return (NegativeImbalance::zero(), value);
+        }
+        let mut data = Self::account(who);
+        data.reserved = data.reserved - actual;
+        Account::<T, I>::insert(who.clone(), data);
+        TotalIssuance::<T, I>::put(Self::total_issuance().saturating_sub(actual));
+
+        system::Pallet::<T>::deposit_event(Event::Slashed {
+            who: who.clone(),
+            amount: actual,
+        });
+        (NegativeImbalance(actual, PhantomData), value - actual)
+    }
+
+    fn repatriate_reserved(
+        slashed: &T::AccountId,
+        beneficiary: &T::AccountId,
+        value: Self::Balance,
+        status: BalanceStatus,
+    ) -> Result<Self::Balance, &'static str> {
+        if slashed == beneficiary {
+            return Ok(match status {
+                BalanceStatus::Free => Self::unreserve(slashed, value),
+                BalanceStatus::Reserved => value - value.min(Self::unnamed_reserve(slashed)),
+            });
+        }
+
+        let actual = value.min(Self::unnamed_reserve(slashed));
+        let mut to = Self::account(beneficiary);
+        match status {
+            BalanceStatus::Free => {
+                to.free = to
+                    .free
+                    .checked_add(&actual)
+                    .ok_or("Overflow when adding to balance")?
+            }
+            BalanceStatus::Reserved => {
+                to.reserved = to
+                    .reserved
+                    .checked_add(&actual)
+                    .ok_or("Overflow when adding to balance")?
+            }
+        }
+        let mut from = Self::account(slashed);
+        from.reserved = from.reserved - actual;
+
+        Account::<T, I>::insert(slashed.clone(), from);
+        Account::<T, I>::insert(beneficiary.clone(), to);
+
+        system::Pallet::<T>::deposit_event(Event::ReserveRepatriated {
+            from: slashed.clone(),
+            to: beneficiary.clone(),
+            amount: actual,
+            destination_status: status,
+        });
+        Ok(value - actual)
+    }
+}
+
+impl<T: Config<I>, I: 'static> NamedReservableCurrency<T::AccountId> for Pallet<T, I> {
+    fn reserved_balance_named(id: &ReserveIdentifier, who: &T::AccountId) -> T::Balance {
+        Holds::<T, I>::get(&(who.clone(), *id)).unwrap_or_else(T::Balance::zero)
+    }
+
+    fn reserve_named(
+        id: &ReserveIdentifier,
+        who: &T::AccountId,
+        value: T::Balance,
+    ) -> DispatchResult {
+        let held = Self::reserved_balance_named(id, who)
+            .checked_add(&value)
+            .ok_or("Overflow when adding to balance")?;
+        Self::reserve(who, value)?;
+        Holds::<T, I>::insert((who.clone(), *id), held);
+        Ok(())
+    }
+
+    fn unreserve_named(
+        id: &ReserveIdentifier,
+        who: &T::AccountId,
+        value: T::Balance,
+    ) -> T::Balance {
+        let held = Self::reserved_balance_named(id, who);
+        let actual = Self::do_unreserve(who, value.min(held));
+        let remaining = held - actual;
+        if remaining.is_zero() {
+            Holds::<T, I>::remove(&(who.clone(), *id));
+        } else {
+            Holds::<T, I>::insert((who.clone(), *id), remaining);
         }
+        value - actual
+    }
+}
+
+impl<T: Config<I>, I: 'static> LockableCurrency<T::AccountId> for Pallet<T, I> {
+    fn set_lock(
+        id: LockIdentifier,
+        who: &T::AccountId,
+        amount: T::Balance,
+        reasons: WithdrawReasons,
+    ) {
+        if amount.is_zero() {
+            return Self::remove_lock(id, who);
+        }
+        let lock = BalanceLock {
+            id,
+            amount,
+            reasons,
+        };
+        Locks::<T, I>::mutate(who, |locks| {
+            let locks = locks.get_or_insert_with(Vec::new);
+            match locks.iter_mut().find(|lock| lock.id == id) {
+                Some(existing) => *existing = lock,
+                None => locks.push(lock),
+            }
+        });
     }
 
-    pub fn set_balance(&mut self, account: &T::AccountId, amount: T::Balance) {
-        self.balances.insert(account.clone(), amount);
+    fn extend_lock(
+        id: LockIdentifier,
+        who: &T::AccountId,
+        amount: T::Balance,
+        reasons: WithdrawReasons,
+    ) {
+        let (amount, reasons) = match Self::locks(who).into_iter().find(|lock| lock.id == id) {
+            Some(existing) if existing.reasons == reasons => (existing.amount.max(amount), reasons),
+            Some(existing) => (existing.amount.max(amount), WithdrawReasons::All),
+            None => (amount, reasons),
+        };
+        Self::set_lock(id, who, amount, reasons);
     }
 
-    pub fn balance(&mut self, account: &T::AccountId) -> T::Balance {
-        *self.balances.get(account).unwrap_or(&T::Balance::zero())
+    fn remove_lock(id: LockIdentifier, who: &T::AccountId) {
+        Locks::<T, I>::mutate(who, |locks| {
+            if let Some(existing) = locks {
+                existing.retain(|lock| lock.id != id);
+                if existing.is_empty() {
+                    *locks = None;
+                }
+            }
+        });
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::types;
+    use crate::{
+        mock,
+        support::{
+            instances::Instance1,
+            traits::{ConstU64, Get},
+            Dispatch,
+        },
+        types,
+    };
 
     use super::*;
 
+    #[derive(Clone, Debug, PartialEq, Eq)]
     struct TestConfig {}
     impl system::Config for TestConfig {
         type AccountId = types::AccountId;
         type BlockNumber = types::BlockNumber;
         type Nonce = types::Nonce;
+        type RuntimeEvent = Event<TestConfig>;
+        type MaximumBlockWeight = ConstU64<1_000>;
+        type ExtrinsicBaseWeight = ConstU64<10>;
+        type RootAccount = types::RootAccount;
     }
 
     impl super::Config for TestConfig {
         type Balance = u128;
     }
 
+    type Balances = Pallet<TestConfig>;
+    type System = system::Pallet<TestConfig>;
+
+    const STAKING: LockIdentifier = *b"staking ";
+    const CLAIMS: ReserveIdentifier = *b"claims  ";
+
     #[test]
     fn init_balances() {
-        let mut balances: Pallet<TestConfig> = Pallet::new();
-        assert_eq!(balances.balance(&"A".to_string()), 0);
-        balances.set_balance(&"A".to_string(), 100);
+        assert_eq!(Balances::balance(&"A".to_string()), 0);
+        Balances::set_balance(&"A".to_string(), 100);
 
-        assert_eq!(balances.balance(&"A".to_string()), 100);
+        assert_eq!(Balances::balance(&"A".to_string()), 100);
     }
 
     #[test]
     fn transfer_balance() {
         let a = "A".to_string();
         let b = "B".to_string();
-        let mut balances: Pallet<TestConfig> = Pallet::new();
-        balances.set_balance(&a, 100);
+        let mut balances = Balances::new();
+        Balances::set_balance(&a, 100);
         let _ = balances.transfer(a.clone(), b.clone(), 50);
 
-        assert_eq!(balances.balance(&a), 50);
-        assert_eq!(balances.balance(&b), 50);
+        assert_eq!(Balances::balance(&a), 50);
+        assert_eq!(Balances::balance(&b), 50);
+        assert_eq!(
+            System::events(),
+            vec![Event::Transfer {
+                from: a,
+                to: b,
+                amount: 50
+            }]
+        );
     }
 
     #[test]
     fn transfer_insufficient_balance() {
         let a = "A".to_string();
         let b = "B".to_string();
-        let mut balances: Pallet<TestConfig> = Pallet::new();
+        let mut balances = Balances::new();
         let result = balances.transfer(a.clone(), b.clone(), 200);
 
         assert_eq!(result, Err("Insufficient fund"));
-        assert_eq!(balances.balance(&a), 0);
-        assert_eq!(balances.balance(&b), 0);
+        assert_eq!(Balances::balance(&a), 0);
+        assert_eq!(Balances::balance(&b), 0);
     }
 
     #[test]
     fn transfer_overflow_balance() {
         let a = "A".to_string();
         let b = "B".to_string();
-        let mut balances: Pallet<TestConfig> = Pallet::new();
-        balances.set_balance(&a, u128::MAX);
-        balances.set_balance(&b, 100);
+        let mut balances = Balances::new();
+        Balances::set_balance(&a, u128::MAX);
+        Balances::set_balance(&b, 100);
         let result = balances.transfer(b.clone(), a.clone(), 50);
 
         assert_eq!(result, Err("Overflow when adding to balance"));
-        assert_eq!(balances.balance(&a), u128::MAX);
-        assert_eq!(balances.balance(&b), 100);
+        assert_eq!(Balances::balance(&a), u128::MAX);
+        assert_eq!(Balances::balance(&b), 100);
+    }
+
+    #[test]
+    fn withdraw_and_deposit_track_issuance() {
+        let a = "A".to_string();
+        let b = "B".to_string();
+        Balances::set_balance(&a, 100);
+        assert_eq!(Balances::total_issuance(), 100);
+
+        let imbalance = Balances::withdraw(&a, 30, WithdrawReasons::Transfer).unwrap();
+        assert_eq!(imbalance.peek(), 30);
+        assert_eq!(Balances::free_balance(&a), 70);
+        assert_eq!(Balances::total_issuance(), 70);
+
+        // Withdrawn funds can be handed to another account.
+        Balances::resolve_creating(&b, imbalance);
+        assert_eq!(Balances::free_balance(&b), 30);
+        assert_eq!(Balances::total_issuance(), 100);
+
+        let minted = Balances::deposit_creating(&b, 20);
+        assert_eq!(minted.merge(PositiveImbalance::zero()).peek(), 20);
+        assert_eq!(Balances::total_issuance(), 120);
+
+        Balances::set_lock(STAKING, &a, 50, WithdrawReasons::All);
+        assert_eq!(
+            Balances::withdraw(&a, 30, WithdrawReasons::Transfer).map(|i| i.peek()),
+            Err("Liquidity restrictions prevent withdrawal")
+        );
+        assert_eq!(
+            Balances::withdraw(&a, 80, WithdrawReasons::Transfer).map(|i| i.peek()),
+            Err("Insufficient fund")
+        );
+    }
+
+    #[test]
+    fn reserve_and_unreserve() {
+        let a = "A".to_string();
+        let b = "B".to_string();
+        let mut balances = Balances::new();
+        Balances::set_balance(&a, 100);
+
+        assert_eq!(Balances::reserve(&a, 60), Ok(()));
+        assert_eq!(Balances::free_balance(&a), 40);
+        assert_eq!(Balances::reserved_balance(&a), 60);
+        assert_eq!(Balances::total_balance(&a), 100);
+
+        // Reserved funds cannot be transferred.
+        assert_eq!(
+            balances.transfer(a.clone(), b.clone(), 50),
+            Err("Insufficient fund")
+        );
+        assert_eq!(Balances::reserve(&a, 50), Err("Insufficient fund"));
+
+        // Unreserving more than is reserved returns the remainder.
+        assert_eq!(Balances::unreserve(&a, 70), 10);
+        assert_eq!(Balances::free_balance(&a), 100);
+        assert_eq!(Balances::reserved_balance(&a), 0);
+        assert_eq!(
+            System::events(),
+            vec![
+                Event::Reserved {
+                    who: a.clone(),
+                    amount: 60
+                },
+                Event::Unreserved { who: a, amount: 60 },
+            ]
+        );
+    }
+
+    #[test]
+    fn repatriate_reserved_balance() {
+        let a = "A".to_string();
+        let b = "B".to_string();
+        Balances::set_balance(&a, 100);
+        Balances::reserve(&a, 50).unwrap();
+
+        assert_eq!(
+            Balances::repatriate_reserved(&a, &b, 20, BalanceStatus::Free),
+            Ok(0)
+        );
+        assert_eq!(
+            Balances::account(&b),
+            AccountData {
+                free: 20,
+                reserved: 0
+            }
+        );
+
+        assert_eq!(
+            Balances::repatriate_reserved(&a, &b, 40, BalanceStatus::Reserved),
+            Ok(10)
+        );
+        assert_eq!(
+            Balances::account(&a),
+            AccountData {
+                free: 50,
+                reserved: 0
+            }
+        );
+        assert_eq!(
+            Balances::account(&b),
+            AccountData {
+                free: 20,
+                reserved: 30
+            }
+        );
+    }
+
+    #[test]
+    fn slash_reserved_leaves_circulation() {
+        let a = "A".to_string();
+        Balances::set_balance(&a, 100);
+        Balances::reserve_named(&CLAIMS, &a, 30).unwrap();
+        Balances::reserve(&a, 20).unwrap();
+
+        // Funds held under a name cannot be slashed anonymously.
+        let (slashed, remaining) = Balances::slash_reserved(&a, 40);
+        assert_eq!((slashed.peek(), remaining), (20, 20));
+        assert_eq!(
+            Balances::account(&a),
+            AccountData {
+                free: 50,
+                reserved: 30
+            }
+        );
+        assert_eq!(Balances::total_issuance(), 80);
+    }
+
+    #[test]
+    fn named_reserves_are_isolated() {
+        let a = "A".to_string();
+        Balances::set_balance(&a, 100);
+        Balances::reserve_named(&CLAIMS, &a, 30).unwrap();
+        Balances::reserve(&a, 20).unwrap();
+        assert_eq!(Balances::reserved_balance(&a), 50);
+        assert_eq!(Balances::reserved_balance_named(&CLAIMS, &a), 30);
+
+        // An anonymous unreserve cannot release funds held under a name.
+        assert_eq!(Balances::unreserve(&a, 50), 30);
+        assert_eq!(Balances::reserved_balance(&a), 30);
+
+        assert_eq!(Balances::unreserve_named(&CLAIMS, &a, 50), 20);
+        assert_eq!(Balances::reserved_balance_named(&CLAIMS, &a), 0);
+        assert_eq!(
+            Balances::account(&a),
+            AccountData {
+                free: 100,
+                reserved: 0
+            }
+        );
+    }
+
+    #[test]
+    fn locks_restrict_transfers() {
+        let a = "A".to_string();
+        let b = "B".to_string();
+        let mut balances = Balances::new();
+        Balances::set_balance(&a, 100);
+        Balances::set_lock(STAKING, &a, 70, WithdrawReasons::Transfer);
+
+        assert_eq!(
+            balances.transfer(a.clone(), b.clone(), 40),
+            Err("Liquidity restrictions prevent withdrawal")
+        );
+        assert_eq!(balances.transfer(a.clone(), b.clone(), 30), Ok(()));
+
+        // A transfer-only lock does not prevent reserving.
+        assert_eq!(Balances::reserve(&a, 70), Ok(()));
+
+        Balances::remove_lock(STAKING, &a);
+        assert!(Balances::locks(&a).is_empty());
+    }
+
+    #[test]
+    fn locks_do_not_stack() {
+        let a = "A".to_string();
+        Balances::set_balance(&a, 100);
+        Balances::set_lock(STAKING, &a, 50, WithdrawReasons::All);
+        Balances::set_lock(*b"vesting ", &a, 30, WithdrawReasons::Reserve);
+        assert_eq!(Balances::frozen_balance(&a, WithdrawReasons::Reserve), 50);
+        assert!(!Balances::can_reserve(&a, 60));
+        assert!(Balances::can_reserve(&a, 50));
+
+        Balances::extend_lock(STAKING, &a, 40, WithdrawReasons::All);
+        assert_eq!(Balances::frozen_balance(&a, WithdrawReasons::Transfer), 50);
+        Balances::extend_lock(STAKING, &a, 80, WithdrawReasons::All);
+        assert_eq!(Balances::frozen_balance(&a, WithdrawReasons::Transfer), 80);
+    }
+
+    #[test]
+    fn only_root_forces_balances() {
+        let a = "A".to_string();
+        let mut balances = Balances::new();
+        assert_eq!(
+            balances.force_set_balance(a.clone(), a.clone(), 100),
+            Err("Caller is not root")
+        );
+        balances
+            .force_set_balance(types::RootAccount::get(), a.clone(), 100)
+            .unwrap();
+        assert_eq!(Balances::balance(&a), 100);
+        assert_eq!(Balances::total_issuance(), 100);
+    }
+
+    /// A second instance of the pallet in the shared test runtime, next to its default instance.
+    impl super::Config<Instance1> for mock::TestConfig {
+        type Balance = u64;
+    }
+
+    #[test]
+    fn instances_have_their_own_storage() {
+        type Native = Pallet<mock::TestConfig>;
+        type Test = Pallet<mock::TestConfig, Instance1>;
+        let (a, b) = ("A".to_string(), "B".to_string());
+        Native::set_balance(&a, 100);
+        Test::set_balance(&a, 10);
+
+        Test::new()
+            .dispatch(
+                a.clone(),
+                Call::<mock::TestConfig, Instance1>::transfer {
+                    to: b.clone(),
+                    amount: 4,
+                },
+            )
+            .unwrap();
+        assert_eq!(Test::balance(&a), 6);
+        assert_eq!(Test::balance(&b), 4);
+        assert_eq!(Native::balance(&a), 100);
+        assert_eq!(Native::balance(&b), 0);
+        assert_eq!(Native::total_issuance(), 100);
+        assert_eq!(Test::total_issuance(), 10);
+        assert_eq!(
+            system::Pallet::<mock::TestConfig>::events(),
+            vec![mock::TestEvent::BalancesInstance1(Event::Transfer {
+                from: a,
+                to: b,
+                amount: 4
+            })]
+        );
     }
 }
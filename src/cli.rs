@@ -0,0 +1,118 @@
+use std::{
+    fs,
+    path::Path,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    identity, proof_of_existence,
+    support::{self, hashing::to_hex, traits::Get},
+    system, timestamp, types, Runtime, RuntimeCall,
+};
+
+pub const USAGE: &str = "usage: rsm [notarize <account> <file> | verify <file>]...
+
+Commands run in order against the same chain, so a file can be verified after it was notarized.
+Without commands, a demo of a few blocks is executed.";
+
+/// Read the file at `path` and submit a claim on its content from `caller`, in a new block. Only
+/// the hash of the file is submitted.
+///
+/// Returns the hash of the file, which the claim is keyed by.
+pub fn notarize_file(
+    runtime: &mut Runtime,
+    caller: types::AccountId,
+    path: impl AsRef<Path>,
+) -> Result<String, String> {
+    let path = path.as_ref();
+    let content = fs::read(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    let hash = proof_of_existence::Pallet::<Runtime>::hash_of(&content);
+
+    let block = support::Block {
+        header: support::Header {
+            block_number: system::Pallet::<Runtime>::block_number() + 1,
+        },
+        inherents: vec![RuntimeCall::timestamp(timestamp::Call::set { now: now() })],
+        extrinsics: vec![support::Extrinsic {
+            caller: caller.clone(),
+            call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
+                claim: hash,
+                expires_at: None,
+            }),
+        }],
+    };
+    runtime.execute_block(block)?;
+
+    // A failing extrinsic does not fail the block, so we look at the claim to know whether ours
+    // went through.
+    match proof_of_existence::Pallet::<Runtime>::claim_info(&hash) {
+        Some(info) if info.owner == caller => Ok(to_hex(&hash)),
+        Some(info) => Err(format!(
+            "{} was already notarized by {}",
+            path.display(),
+            describe(&info.owner)
+        )),
+        None => Err(format!("the claim on {} was rejected", path.display())),
+    }
+}
+
+/// The time of a new block: the current time, unless blocks come faster than the minimum period.
+fn now() -> types::Moment {
+    let wall_clock = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as types::Moment);
+    let earliest =
+        timestamp::Pallet::<Runtime>::now() + <Runtime as timestamp::Config>::MinimumPeriod::get();
+    wall_clock.max(earliest)
+}
+
+/// Describe the claim on the content of the file at `path`, if any.
+pub fn verify_file(path: impl AsRef<Path>) -> Result<String, String> {
+    let path = path.as_ref();
+    let content = fs::read(path).map_err(|e| format!("cannot read {}: {}", path.display(), e))?;
+    let hash = proof_of_existence::Pallet::<Runtime>::hash_of(&content);
+
+    Ok(
+        match proof_of_existence::Pallet::<Runtime>::claim_info(&hash) {
+            Some(info) => {
+                let mut report = format!(
+                    "{} ({}) is owned by {} since block {}",
+                    path.display(),
+                    to_hex(&hash),
+                    describe(&info.owner),
+                    info.block_number
+                );
+                if let Some(n) = info.expires_at {
+                    report.push_str(&format!(", until block {}", n));
+                }
+                report
+            }
+            None => format!("{} ({}) is not notarized", path.display(), to_hex(&hash)),
+        },
+    )
+}
+
+/// The account id followed by the display name of its identity, if it has one.
+fn describe(account: &types::AccountId) -> String {
+    match identity::Pallet::<Runtime>::display_name(account) {
+        Some(name) => format!("{} ({})", account, name),
+        None => account.clone(),
+    }
+}
+
+/// Run the commands in `args` in order.
+pub fn run(runtime: &mut Runtime, args: &[String]) -> Result<(), String> {
+    let mut args = args.iter();
+    while let Some(command) = args.next() {
+        match (command.as_str(), args.next()) {
+            ("notarize", Some(account)) => {
+                let path = args.next().ok_or(USAGE)?;
+                let hash = notarize_file(runtime, account.clone(), path)?;
+                println!("{} notarized {} ({})", account, path, hash);
+            }
+            ("verify", Some(path)) => println!("{}", verify_file(path)?),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    Ok(())
+}
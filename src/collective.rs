@@ -0,0 +1,497 @@
+use std::marker::PhantomData;
+
+use num::traits::CheckedAdd;
+
+use crate::{
+    support::{
+        codec::Encode,
+        hashing::{hash_of, H256},
+        storage::{transactional, StorageMap, StorageValue},
+        traits::{Convert, EnsureOrigin, Get, Hooks},
+        DispatchResult, Dispatchable,
+    },
+    system,
+};
+
+pub type ProposalIndex = u32;
+
+pub trait Config: system::Config<RuntimeEvent: From<Event<Self>>> {
+    /// The calls the collective votes on, usually the `RuntimeCall` of the runtime.
+    type RuntimeCall: Dispatchable<Caller = Self::AccountId> + Clone + Encode;
+    /// Turns the hash identifying the collective and a threshold into the account id approved
+    /// motions are dispatched as.
+    type AccountIdFromHash: Convert<H256, Self::AccountId>;
+    /// How long, in blocks, members can vote on a motion before it can be closed with the default
+    /// votes of the prime member.
+    type MotionDuration: Get<Self::BlockNumber>;
+    /// The largest number of motions open at once.
+    type MaxProposals: Get<u32>;
+    type MaxMembers: Get<u32>;
+}
+
+/// The votes on an open motion.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Votes<AccountId, BlockNumber> {
+    pub index: ProposalIndex,
+    /// The number of approvals the motion needs.
+    pub threshold: u32,
+    pub ayes: Vec<AccountId>,
+    pub nays: Vec<AccountId>,
+    /// The block from which the motion can be closed even if the vote is undecided.
+    pub end: BlockNumber,
+}
+
+pub type VotesOf<T> = Votes<<T as system::Config>::AccountId, <T as system::Config>::BlockNumber>;
+
+/// Motions are identified by the hash of their call.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event<T: Config> {
+    Proposed {
+        account: T::AccountId,
+        proposal_index: ProposalIndex,
+        proposal_hash: H256,
+        threshold: u32,
+    },
+    Voted {
+        account: T::AccountId,
+        proposal_hash: H256,
+        voted: bool,
+        yes: u32,
+        no: u32,
+    },
+    Closed {
+        proposal_hash: H256,
+        yes: u32,
+        no: u32,
+    },
+    Approved {
+        proposal_hash: H256,
+    },
+    Disapproved {
+        proposal_hash: H256,
+    },
+    /// An approved motion was dispatched as the collective, with the given result.
+    Executed {
+        proposal_hash: H256,
+        result: DispatchResult,
+    },
+    MembersChanged {
+        members: Vec<T::AccountId>,
+        prime: Option<T::AccountId>,
+    },
+}
+
+/// The members of the collective, sorted.
+pub struct Members<T>(PhantomData<T>);
+impl<T: Config> StorageValue for Members<T> {
+    type Value = Vec<T::AccountId>;
+}
+
+/// The member whose vote is the default of the members who do not vote.
+pub struct Prime<T>(PhantomData<T>);
+impl<T: Config> StorageValue for Prime<T> {
+    type Value = T::AccountId;
+}
+
+/// The hashes of the open motions, in the order they were proposed.
+pub struct Proposals<T>(PhantomData<T>);
+impl<T: Config> StorageValue for Proposals<T> {
+    type Value = Vec<H256>;
+}
+
+/// The call of each open motion.
+pub struct ProposalOf<T>(PhantomData<T>);
+impl<T: Config> StorageMap for ProposalOf<T> {
+    type Key = H256;
+    type Value = T::RuntimeCall;
+}
+
+pub struct Voting<T>(PhantomData<T>);
+impl<T: Config> StorageMap for Voting<T> {
+    type Key = H256;
+    type Value = VotesOf<T>;
+}
+
+/// The number of motions proposed so far.
+pub struct ProposalCount<T>(PhantomData<T>);
+impl<T: Config> StorageValue for ProposalCount<T> {
+    type Value = ProposalIndex;
+}
+
+/// An `EnsureOrigin` implementation accepting the account of the collective for motions approved
+/// by at least `N` members.
+pub struct EnsureMembers<T, const N: u32>(PhantomData<T>);
+impl<T: Config, const N: u32> EnsureOrigin<T::AccountId> for EnsureMembers<T, N> {
+    fn ensure_origin(caller: &T::AccountId) -> DispatchResult {
+        if (N..=T::MaxMembers::get()).any(|threshold| Pallet::<T>::account_id(threshold) == *caller)
+        {
+            return Ok(());
+        }
+        Err("Caller is not the collective")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pallet<T: Config>(PhantomData<T>);
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+    /// Replace the members of the collective and its prime member. Only root can call it.
+    ///
+    /// The votes of outgoing members on open motions are removed.
+    pub fn set_members(
+        &mut self,
+        caller: T::AccountId,
+        mut new_members: Vec<T::AccountId>,
+        prime: Option<T::AccountId>,
+    ) -> DispatchResult {
+        system::Pallet::<T>::ensure_root(&caller)?;
+        new_members.sort();
+        new_members.dedup();
+        if new_members.len() > T::MaxMembers::get() as usize {
+            return Err("Too many members");
+        }
+        if prime
+            .as_ref()
+            .is_some_and(|prime| !new_members.contains(prime))
+        {
+            return Err("Prime must be a member");
+        }
+
+        for hash in Self::proposals() {
+            Voting::<T>::mutate(&hash, |votes| {
+                if let Some(votes) = votes {
+                    votes.ayes.retain(|who| new_members.contains(who));
+                    votes.nays.retain(|who| new_members.contains(who));
+                }
+            });
+        }
+        Members::<T>::put(new_members.clone());
+        match &prime {
+            Some(prime) => Prime::<T>::put(prime.clone()),
+            None => {
+                Prime::<T>::kill();
+            }
+        }
+        system::Pallet::<T>::deposit_event(Event::MembersChanged {
+            members: new_members,
+            prime,
+        });
+        Ok(())
+    }
+
+    /// Propose a motion that `threshold` members must approve. A motion with a threshold of one
+    /// is dispatched right away.
+    // The call is boxed because a `RuntimeCall` can contain a call of this pallet.
+    #[allow(clippy::boxed_local)]
+    pub fn propose(
+        &mut self,
+        caller: T::AccountId,
+        threshold: u32,
+        proposal: Box<T::RuntimeCall>,
+    ) -> DispatchResult {
+        let members = Self::ensure_member(&caller)?;
+        if threshold == 0 || threshold as usize > members.len() {
+            return Err("Invalid threshold");
+        }
+        let proposal_hash = hash_of(&proposal);
+        if ProposalOf::<T>::contains_key(&proposal_hash) {
+            return Err("Duplicate proposal");
+        }
+
+        if threshold == 1 {
+            Self::execute(proposal_hash, threshold, *proposal);
+            return Ok(());
+        }
+
+        let mut proposals = Self::proposals();
+        if proposals.len() >= T::MaxProposals::get() as usize {
+            return Err("Too many proposals");
+        }
+        let proposal_index = ProposalCount::<T>::get().unwrap_or(0);
+        let end = system::Pallet::<T>::block_number()
+            .checked_add(&T::MotionDuration::get())
+            .ok_or("Block number overflow")?;
+
+        ProposalCount::<T>::put(
+            proposal_index
+                .checked_add(1)
+                .ok_or("Proposal indices are exhausted")?,
+        );
+        proposals.push(proposal_hash);
+        Proposals::<T>::put(proposals);
+        ProposalOf::<T>::insert(proposal_hash, *proposal);
+        Voting::<T>::insert(
+            proposal_hash,
+            Votes {
+                index: proposal_index,
+                threshold,
+                ayes: Vec::new(),
+                nays: Vec::new(),
+                end,
+            },
+        );
+        system::Pallet::<T>::deposit_event(Event::Proposed {
+            account: caller,
+            proposal_index,
+            proposal_hash,
+            threshold,
+        });
+        Ok(())
+    }
+
+    /// Vote on an open motion, replacing any previous vote of the caller on it.
+    pub fn vote(&mut self, caller: T::AccountId, proposal: H256, approve: bool) -> DispatchResult {
+        Self::ensure_member(&caller)?;
+        let mut votes = Voting::<T>::get(&proposal).ok_or("Proposal not found")?;
+        let (same, other) = if approve {
+            (&mut votes.ayes, &mut votes.nays)
+        } else {
+            (&mut votes.nays, &mut votes.ayes)
+        };
+        if same.contains(&caller) {
+            return Err("Duplicate vote");
+        }
+        same.push(caller.clone());
+        other.retain(|who| *who != caller);
+
+        let (yes, no) = (votes.ayes.len() as u32, votes.nays.len() as u32);
+        Voting::<T>::insert(proposal, votes);
+        system::Pallet::<T>::deposit_event(Event::Voted {
+            account: caller,
+            proposal_hash: proposal,
+            voted: approve,
+            yes,
+            no,
+        });
+        Ok(())
+    }
+
+    /// Close a motion once its vote is decided, or once its duration has passed. In that case,
+    /// the members who did not vote vote like the prime member, or against if there is none.
+    ///
+    /// An approved motion is dispatched as the account of the collective for its threshold.
+    pub fn close(&mut self, _caller: T::AccountId, proposal: H256) -> DispatchResult {
+        let votes = Voting::<T>::get(&proposal).ok_or("Proposal not found")?;
+        let seats = Self::members().len() as u32;
+        let mut yes = votes.ayes.len() as u32;
+        let mut no = votes.nays.len() as u32;
+        let approved = yes >= votes.threshold;
+        let disapproved = seats.saturating_sub(no) < votes.threshold;
+
+        if !approved && !disapproved {
+            if system::Pallet::<T>::block_number() < votes.end {
+                return Err("Too early to close the proposal");
+            }
+            let abstentions = seats.saturating_sub(yes + no);
+            let prime_aye = Prime::<T>::get().is_some_and(|prime| votes.ayes.contains(&prime));
+            if prime_aye {
+                yes += abstentions;
+            } else {
+                no += abstentions;
+            }
+        }
+
+        let call = Self::remove_proposal(proposal).expect("every voted proposal is stored");
+        system::Pallet::<T>::deposit_event(Event::Closed {
+            proposal_hash: proposal,
+            yes,
+            no,
+        });
+        if yes >= votes.threshold {
+            system::Pallet::<T>::deposit_event(Event::Approved {
+                proposal_hash: proposal,
+            });
+            Self::execute(proposal, votes.threshold, call);
+        } else {
+            system::Pallet::<T>::deposit_event(Event::Disapproved {
+                proposal_hash: proposal,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn new() -> Self {
+        Pallet(PhantomData)
+    }
+
+    pub fn members() -> Vec<T::AccountId> {
+        Members::<T>::get().unwrap_or_default()
+    }
+
+    pub fn prime() -> Option<T::AccountId> {
+        Prime::<T>::get()
+    }
+
+    pub fn proposals() -> Vec<H256> {
+        Proposals::<T>::get().unwrap_or_default()
+    }
+
+    pub fn voting(proposal: &H256) -> Option<VotesOf<T>> {
+        Voting::<T>::get(proposal)
+    }
+
+    /// The account motions approved with `threshold` are dispatched as.
+    ///
+    /// No one holds its key. Pallets accept it as a caller through `EnsureMembers`.
+    pub fn account_id(threshold: u32) -> T::AccountId {
+        T::AccountIdFromHash::convert(hash_of(&(b"collective", threshold)))
+    }
+
+    fn ensure_member(who: &T::AccountId) -> Result<Vec<T::AccountId>, &'static str> {
+        let members = Self::members();
+        if !members.contains(who) {
+            return Err("Caller is not a member");
+        }
+        Ok(members)
+    }
+
+    fn remove_proposal(proposal: H256) -> Option<T::RuntimeCall> {
+        Proposals::<T>::mutate(|proposals| {
+            if let Some(proposals) = proposals {
+                proposals.retain(|hash| *hash != proposal);
+            }
+        });
+        Voting::<T>::remove(&proposal);
+        ProposalOf::<T>::remove(&proposal)
+    }
+
+    /// Dispatch an approved motion as the account of the collective for `threshold`.
+    fn execute(proposal_hash: H256, threshold: u32, call: T::RuntimeCall) {
+        let result = transactional(|| call.dispatch(Self::account_id(threshold)));
+        system::Pallet::<T>::deposit_event(Event::Executed {
+            proposal_hash,
+            result,
+        });
+    }
+}
+
+impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        balances,
+        mock::{transfer, TestCall, TestConfig, TestEvent},
+        support::traits::ConstU32,
+        types::{self, HexAccountId},
+    };
+
+    use super::*;
+
+    impl Config for TestConfig {
+        type RuntimeCall = TestCall;
+        type AccountIdFromHash = HexAccountId;
+        type MotionDuration = ConstU32<3>;
+        type MaxProposals = ConstU32<2>;
+        type MaxMembers = ConstU32<5>;
+    }
+
+    type System = system::Pallet<TestConfig>;
+    type Balances = balances::Pallet<TestConfig>;
+    type Collective = Pallet<TestConfig>;
+
+    /// A collective of A, B and C, with `prime` as prime member.
+    fn set_up(prime: Option<&str>) -> Collective {
+        let mut collective = Collective::new();
+        let members = ["C", "A", "B"].map(String::from).to_vec();
+        assert_eq!(
+            collective.set_members("A".to_string(), members.clone(), None),
+            Err("Caller is not root")
+        );
+        collective
+            .set_members(types::RootAccount::get(), members, prime.map(String::from))
+            .unwrap();
+        Balances::set_balance(&Collective::account_id(2), 100);
+        Balances::set_balance(&Collective::account_id(3), 100);
+        collective
+    }
+
+    #[test]
+    fn approved_motion_is_dispatched_as_the_collective() {
+        let (a, b, c) = ("A".to_string(), "B".to_string(), "C".to_string());
+        let mut collective = set_up(None);
+        assert_eq!(Collective::members(), vec![a.clone(), b.clone(), c.clone()]);
+
+        assert_eq!(
+            collective.propose("D".to_string(), 2, transfer("D", 10)),
+            Err("Caller is not a member")
+        );
+        assert_eq!(
+            collective.propose(a.clone(), 4, transfer("D", 10)),
+            Err("Invalid threshold")
+        );
+        collective.propose(a.clone(), 2, transfer("D", 10)).unwrap();
+        let hash = Collective::proposals()[0];
+        assert_eq!(
+            collective.propose(b.clone(), 2, transfer("D", 10)),
+            Err("Duplicate proposal")
+        );
+
+        collective.vote(a.clone(), hash, true).unwrap();
+        assert_eq!(
+            collective.vote(a.clone(), hash, true),
+            Err("Duplicate vote")
+        );
+        assert_eq!(
+            collective.close(a.clone(), hash),
+            Err("Too early to close the proposal")
+        );
+        collective.vote(c.clone(), hash, true).unwrap();
+        collective.close(b, hash).unwrap();
+
+        assert_eq!(Balances::balance(&"D".to_string()), 10);
+        assert!(Collective::proposals().is_empty());
+        assert!(
+            System::events().contains(&TestEvent::Collective(Event::Executed {
+                proposal_hash: hash,
+                result: Ok(())
+            }))
+        );
+    }
+
+    #[test]
+    fn absent_members_follow_the_prime_member() {
+        let (a, b) = ("A".to_string(), "B".to_string());
+        let mut collective = set_up(Some("B"));
+        collective.propose(a.clone(), 3, transfer("D", 10)).unwrap();
+        collective.propose(a.clone(), 2, transfer("D", 20)).unwrap();
+        let (first, second) = (Collective::proposals()[0], Collective::proposals()[1]);
+
+        collective.vote(a.clone(), first, true).unwrap();
+        collective.vote(b.clone(), first, true).unwrap();
+        collective.vote(b.clone(), second, false).unwrap();
+        System::inc_block_number();
+        System::inc_block_number();
+        System::inc_block_number();
+
+        // C follows B, who approved the first motion and rejected the second.
+        collective.close(a.clone(), first).unwrap();
+        collective.close(a, second).unwrap();
+        assert_eq!(Balances::balance(&"D".to_string()), 10);
+        assert!(
+            System::events().contains(&TestEvent::Collective(Event::Disapproved {
+                proposal_hash: second
+            }))
+        );
+    }
+
+    #[test]
+    fn ensure_members_checks_the_threshold() {
+        set_up(None);
+        assert_eq!(
+            EnsureMembers::<TestConfig, 2>::ensure_origin(&Collective::account_id(3)),
+            Ok(())
+        );
+        assert_eq!(
+            EnsureMembers::<TestConfig, 2>::ensure_origin(&Collective::account_id(1)),
+            Err("Caller is not the collective")
+        );
+        assert_eq!(
+            EnsureMembers::<TestConfig, 2>::ensure_origin(&"A".to_string()),
+            Err("Caller is not the collective")
+        );
+    }
+}
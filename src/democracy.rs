@@ -0,0 +1,767 @@
+use std::{fmt::Debug, marker::PhantomData};
+
+use num::traits::{CheckedAdd, One, Saturating, SaturatingMul, Unsigned, Zero};
+
+use crate::{
+    scheduler,
+    support::{
+        codec::Encode,
+        storage::{StorageMap, StorageValue},
+        traits::{
+            Currency, Get, Hooks, LockIdentifier, LockableCurrency, ReservableCurrency,
+            WithdrawReasons,
+        },
+        DispatchResult, Weight,
+    },
+    system,
+};
+
+/// The name of the lock placed on the balance used to vote.
+pub const DEMOCRACY_ID: LockIdentifier = *b"democrac";
+
+pub type PropIndex = u32;
+pub type ReferendumIndex = u32;
+
+pub type BalanceOf<T> =
+    <<T as Config>::Currency as Currency<<T as system::Config>::AccountId>>::Balance;
+
+/// The calls proposals are made of, which are the calls the scheduler dispatches.
+pub type CallOf<T> = <T as scheduler::Config>::RuntimeCall;
+
+/// Passed referenda are enacted through the scheduler, as the root account.
+pub trait Config: scheduler::Config + system::Config<RuntimeEvent: From<Event<Self>>> {
+    /// The currency deposits are reserved in and votes are weighted by.
+    type Currency: ReservableCurrency<Self::AccountId>
+        + LockableCurrency<
+            Self::AccountId,
+            Balance: Unsigned + Saturating + SaturatingMul + From<u8> + Copy + Ord + Debug,
+        >;
+    /// The smallest deposit a proposal can be made with.
+    type MinimumDeposit: Get<BalanceOf<Self>>;
+    /// How often, in blocks, the most backed proposal becomes a referendum.
+    type LaunchPeriod: Get<Self::BlockNumber>;
+    /// How long, in blocks, a referendum can be voted on.
+    type VotingPeriod: Get<Self::BlockNumber>;
+    /// How long, in blocks, a passed referendum waits before it is enacted. At least one block.
+    type EnactmentPeriod: Get<Self::BlockNumber>;
+    /// How long, in blocks, a vote with `Conviction::Locked1x` stays locked after its referendum.
+    type VoteLockingPeriod: Get<Self::BlockNumber>;
+    /// The largest number of proposals waiting to become referenda.
+    type MaxProposals: Get<u32>;
+}
+
+/// How much a voter commits to their vote. Voting with more conviction multiplies the votes of
+/// the balance, but locks it for longer once the referendum is over.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Conviction {
+    /// A tenth of a vote per unit of balance, not locked after the referendum.
+    None,
+    Locked1x,
+    Locked2x,
+    Locked3x,
+    Locked4x,
+    Locked5x,
+    Locked6x,
+}
+
+impl Conviction {
+    /// The number of `VoteLockingPeriod`s the balance stays locked after the referendum.
+    pub fn lock_periods(self) -> u32 {
+        match self {
+            Conviction::None => 0,
+            Conviction::Locked1x => 1,
+            Conviction::Locked2x => 2,
+            Conviction::Locked3x => 4,
+            Conviction::Locked4x => 8,
+            Conviction::Locked5x => 16,
+            Conviction::Locked6x => 32,
+        }
+    }
+
+    /// The votes `balance` is worth with this conviction.
+    pub fn votes<Balance>(self, balance: Balance) -> Balance
+    where
+        Balance: Unsigned + SaturatingMul + From<u8> + Copy,
+    {
+        let multiplier: u8 = match self {
+            Conviction::None => return balance / Balance::from(10),
+            Conviction::Locked1x => 1,
+            Conviction::Locked2x => 2,
+            Conviction::Locked3x => 3,
+            Conviction::Locked4x => 4,
+            Conviction::Locked5x => 5,
+            Conviction::Locked6x => 6,
+        };
+        balance.saturating_mul(&Balance::from(multiplier))
+    }
+}
+
+impl Encode for Conviction {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        dest.push(*self as u8);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AccountVote<Balance> {
+    pub aye: bool,
+    pub conviction: Conviction,
+    /// The balance voting, which is locked.
+    pub balance: Balance,
+}
+
+impl<Balance: Encode> Encode for AccountVote<Balance> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.aye.encode_to(dest);
+        self.conviction.encode_to(dest);
+        self.balance.encode_to(dest);
+    }
+}
+
+/// The votes a referendum received so far.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Tally<Balance> {
+    /// Votes in favour, weighted by conviction.
+    pub ayes: Balance,
+    /// Votes against, weighted by conviction.
+    pub nays: Balance,
+    /// The balance that voted, whatever the conviction.
+    pub turnout: Balance,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReferendumInfo<BlockNumber, Call, Balance> {
+    Ongoing {
+        /// The block at the start of which the referendum is decided.
+        end: BlockNumber,
+        proposal: Call,
+        tally: Tally<Balance>,
+    },
+    Finished {
+        approved: bool,
+        end: BlockNumber,
+    },
+}
+
+pub type ReferendumInfoOf<T> =
+    ReferendumInfo<<T as system::Config>::BlockNumber, CallOf<T>, BalanceOf<T>>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event<T: Config> {
+    Proposed {
+        proposal_index: PropIndex,
+        deposit: BalanceOf<T>,
+    },
+    Seconded {
+        seconder: T::AccountId,
+        proposal_index: PropIndex,
+    },
+    /// The most backed proposal became a referendum.
+    Started {
+        ref_index: ReferendumIndex,
+        proposal_index: PropIndex,
+    },
+    Voted {
+        voter: T::AccountId,
+        ref_index: ReferendumIndex,
+        vote: AccountVote<BalanceOf<T>>,
+    },
+    /// The referendum passed, and its proposal is scheduled for enactment at the end of the
+    /// enactment period, or in the first block after it with room in the agenda.
+    Passed {
+        ref_index: ReferendumIndex,
+    },
+    NotPassed {
+        ref_index: ReferendumIndex,
+    },
+    /// The referendum passed, but its proposal could not be scheduled.
+    EnactmentFailed {
+        ref_index: ReferendumIndex,
+        error: &'static str,
+    },
+}
+
+/// The index of the next proposal.
+pub struct PublicPropCount<T>(PhantomData<T>);
+impl<T: Config> StorageValue for PublicPropCount<T> {
+    type Value = PropIndex;
+}
+
+/// The proposals waiting to become referenda, with the account that made each of them.
+pub struct PublicProps<T>(PhantomData<T>);
+impl<T: Config> StorageValue for PublicProps<T> {
+    type Value = Vec<(PropIndex, CallOf<T>, T::AccountId)>;
+}
+
+/// The accounts backing each proposal, and the deposit each of them reserved.
+pub struct DepositOf<T>(PhantomData<T>);
+impl<T: Config> StorageMap for DepositOf<T> {
+    type Key = PropIndex;
+    type Value = (Vec<T::AccountId>, BalanceOf<T>);
+}
+
+/// The index of the next referendum.
+pub struct ReferendumCount<T>(PhantomData<T>);
+impl<T: Config> StorageValue for ReferendumCount<T> {
+    type Value = ReferendumIndex;
+}
+
+/// The state of each referendum.
+pub struct ReferendumInfoFor<T>(PhantomData<T>);
+impl<T: Config> StorageMap for ReferendumInfoFor<T> {
+    type Key = ReferendumIndex;
+    type Value = ReferendumInfoOf<T>;
+}
+
+/// The ongoing referenda decided at the start of each block.
+pub struct ReferendumsEndingAt<T>(PhantomData<T>);
+impl<T: Config> StorageMap for ReferendumsEndingAt<T> {
+    type Key = T::BlockNumber;
+    type Value = Vec<ReferendumIndex>;
+}
+
+/// The votes of each account, with the referendum of each, kept until the balance they lock is
+/// unlocked.
+pub struct VotingOf<T>(PhantomData<T>);
+impl<T: Config> StorageMap for VotingOf<T> {
+    type Key = T::AccountId;
+    type Value = Vec<(ReferendumIndex, AccountVote<BalanceOf<T>>)>;
+}
+
+/// The block in which a proposal was last due to become a referendum.
+pub struct LastLaunch<T>(PhantomData<T>);
+impl<T: Config> StorageValue for LastLaunch<T> {
+    type Value = T::BlockNumber;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pallet<T: Config>(PhantomData<T>);
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+    /// Propose `proposal` to be dispatched as root, reserving `value` of the caller as a deposit
+    /// until it becomes a referendum.
+    // The call is boxed because a `RuntimeCall` can contain a call of this pallet.
+    #[allow(clippy::boxed_local)]
+    pub fn propose(
+        &mut self,
+        caller: T::AccountId,
+        proposal: Box<CallOf<T>>,
+        value: BalanceOf<T>,
+    ) -> DispatchResult {
+        if value < T::MinimumDeposit::get() {
+            return Err("Deposit is below the minimum");
+        }
+        let mut props = PublicProps::<T>::get().unwrap_or_default();
+        if props.len() >= T::MaxProposals::get() as usize {
+            return Err("Too many proposals");
+        }
+        <T as Config>::Currency::reserve(&caller, value)?;
+
+        let proposal_index = PublicPropCount::<T>::get().unwrap_or(0);
+        PublicPropCount::<T>::put(
+            proposal_index
+                .checked_add(1)
+                .ok_or("Proposal indices are exhausted")?,
+        );
+        props.push((proposal_index, *proposal, caller.clone()));
+        PublicProps::<T>::put(props);
+        DepositOf::<T>::insert(proposal_index, (vec![caller], value));
+        system::Pallet::<T>::deposit_event(Event::Proposed {
+            proposal_index,
+            deposit: value,
+        });
+        Ok(())
+    }
+
+    /// Back a proposal by reserving the same deposit as its proposer.
+    pub fn second(&mut self, caller: T::AccountId, proposal: PropIndex) -> DispatchResult {
+        let (mut depositors, deposit) =
+            DepositOf::<T>::get(&proposal).ok_or("Proposal not found")?;
+        <T as Config>::Currency::reserve(&caller, deposit)?;
+
+        depositors.push(caller.clone());
+        DepositOf::<T>::insert(proposal, (depositors, deposit));
+        system::Pallet::<T>::deposit_event(Event::Seconded {
+            seconder: caller,
+            proposal_index: proposal,
+        });
+        Ok(())
+    }
+
+    /// Vote on an ongoing referendum, replacing any previous vote of the caller on it.
+    ///
+    /// The voting balance is locked until the referendum is over, and then for as long as the
+    /// conviction of the vote requires.
+    pub fn vote(
+        &mut self,
+        caller: T::AccountId,
+        ref_index: ReferendumIndex,
+        vote: AccountVote<BalanceOf<T>>,
+    ) -> DispatchResult {
+        let ReferendumInfo::Ongoing {
+            end,
+            proposal,
+            mut tally,
+        } = Self::referendum_info(ref_index)?
+        else {
+            return Err("Referendum is not ongoing");
+        };
+        if vote.balance > <T as Config>::Currency::free_balance(&caller) {
+            return Err("Insufficient free balance to vote");
+        }
+
+        let mut votes = Self::votes_of(&caller);
+        match votes.iter_mut().find(|(index, _)| *index == ref_index) {
+            Some((_, previous)) => {
+                Self::remove_from_tally(&mut tally, previous);
+                *previous = vote;
+            }
+            None => votes.push((ref_index, vote)),
+        }
+        Self::add_to_tally(&mut tally, &vote);
+        ReferendumInfoFor::<T>::insert(
+            ref_index,
+            ReferendumInfo::Ongoing {
+                end,
+                proposal,
+                tally,
+            },
+        );
+        Self::set_votes(&caller, votes);
+        Self::update_lock(&caller);
+        system::Pallet::<T>::deposit_event(Event::Voted {
+            voter: caller,
+            ref_index,
+            vote,
+        });
+        Ok(())
+    }
+
+    /// Withdraw the vote of the caller from an ongoing referendum.
+    pub fn remove_vote(
+        &mut self,
+        caller: T::AccountId,
+        ref_index: ReferendumIndex,
+    ) -> DispatchResult {
+        let ReferendumInfo::Ongoing {
+            end,
+            proposal,
+            mut tally,
+        } = Self::referendum_info(ref_index)?
+        else {
+            return Err("Referendum is not ongoing");
+        };
+        let mut votes = Self::votes_of(&caller);
+        let position = votes
+            .iter()
+            .position(|(index, _)| *index == ref_index)
+            .ok_or("Vote not found")?;
+        let (_, vote) = votes.remove(position);
+        Self::set_votes(&caller, votes);
+
+        Self::remove_from_tally(&mut tally, &vote);
+        ReferendumInfoFor::<T>::insert(
+            ref_index,
+            ReferendumInfo::Ongoing {
+                end,
+                proposal,
+                tally,
+            },
+        );
+        Self::update_lock(&caller);
+        Ok(())
+    }
+
+    /// Unlock the balance `target` voted with in referenda that are over, once their conviction
+    /// no longer locks it.
+    pub fn unlock(&mut self, _caller: T::AccountId, target: T::AccountId) -> DispatchResult {
+        let now = system::Pallet::<T>::block_number();
+        let mut votes = Self::votes_of(&target);
+        votes.retain(|(ref_index, vote)| {
+            let Some(ReferendumInfo::Finished { end, .. }) = ReferendumInfoFor::<T>::get(ref_index)
+            else {
+                return true;
+            };
+            let unlocked_at = Self::lock_duration(vote.conviction)
+                .and_then(|duration| end.checked_add(&duration));
+            unlocked_at.is_none_or(|unlocked_at| now < unlocked_at)
+        });
+        Self::set_votes(&target, votes);
+        Self::update_lock(&target);
+        Ok(())
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn new() -> Self {
+        Pallet(PhantomData)
+    }
+
+    pub fn referendum_info(
+        ref_index: ReferendumIndex,
+    ) -> Result<ReferendumInfoOf<T>, &'static str> {
+        ReferendumInfoFor::<T>::get(&ref_index).ok_or("Referendum not found")
+    }
+
+    /// The proposals waiting to become referenda, with their proposers.
+    pub fn public_props() -> Vec<(PropIndex, CallOf<T>, T::AccountId)> {
+        PublicProps::<T>::get().unwrap_or_default()
+    }
+
+    /// The votes of `who`, with the referendum of each.
+    pub fn votes_of(who: &T::AccountId) -> Vec<(ReferendumIndex, AccountVote<BalanceOf<T>>)> {
+        VotingOf::<T>::get(who).unwrap_or_default()
+    }
+
+    fn set_votes(who: &T::AccountId, votes: Vec<(ReferendumIndex, AccountVote<BalanceOf<T>>)>) {
+        if votes.is_empty() {
+            VotingOf::<T>::remove(who);
+        } else {
+            VotingOf::<T>::insert(who.clone(), votes);
+        }
+    }
+
+    fn add_to_tally(tally: &mut Tally<BalanceOf<T>>, vote: &AccountVote<BalanceOf<T>>) {
+        let votes = vote.conviction.votes(vote.balance);
+        if vote.aye {
+            tally.ayes = tally.ayes.saturating_add(votes);
+        } else {
+            tally.nays = tally.nays.saturating_add(votes);
+        }
+        tally.turnout = tally.turnout.saturating_add(vote.balance);
+    }
+
+    fn remove_from_tally(tally: &mut Tally<BalanceOf<T>>, vote: &AccountVote<BalanceOf<T>>) {
+        let votes = vote.conviction.votes(vote.balance);
+        if vote.aye {
+            tally.ayes = tally.ayes.saturating_sub(votes);
+        } else {
+            tally.nays = tally.nays.saturating_sub(votes);
+        }
+        tally.turnout = tally.turnout.saturating_sub(vote.balance);
+    }
+
+    /// How long a vote with `conviction` stays locked after its referendum, or `None` if it
+    /// overflows the block number.
+    fn lock_duration(conviction: Conviction) -> Option<T::BlockNumber> {
+        let period = T::VoteLockingPeriod::get();
+        (0..conviction.lock_periods()).try_fold(T::BlockNumber::zero(), |duration, _| {
+            duration.checked_add(&period)
+        })
+    }
+
+    /// Lock the largest balance `who` still has a vote with.
+    fn update_lock(who: &T::AccountId) {
+        let locked = Self::votes_of(who)
+            .into_iter()
+            .map(|(_, vote)| vote.balance)
+            .max()
+            .unwrap_or_else(BalanceOf::<T>::zero);
+        if locked.is_zero() {
+            <T as Config>::Currency::remove_lock(DEMOCRACY_ID, who);
+        } else {
+            <T as Config>::Currency::set_lock(DEMOCRACY_ID, who, locked, WithdrawReasons::Transfer);
+        }
+    }
+
+    /// The sum of the deposits backing a proposal.
+    fn backing_of(proposal: PropIndex) -> BalanceOf<T> {
+        let Some((depositors, deposit)) = DepositOf::<T>::get(&proposal) else {
+            return BalanceOf::<T>::zero();
+        };
+        depositors
+            .iter()
+            .fold(BalanceOf::<T>::zero(), |acc, _| acc.saturating_add(deposit))
+    }
+
+    /// Turn the proposal with the most backing into a referendum, and give the deposits back.
+    /// Ties go to the oldest proposal. Returns the index of the referendum.
+    ///
+    /// Nothing changes if no referendum can start.
+    fn launch_public(now: T::BlockNumber) -> Result<ReferendumIndex, &'static str> {
+        let end = now
+            .checked_add(&T::VotingPeriod::get())
+            .ok_or("Block number overflow")?;
+        let ref_index = ReferendumCount::<T>::get().unwrap_or(0);
+        let next_index = ref_index
+            .checked_add(1)
+            .ok_or("Referendum indices are exhausted")?;
+        let mut props = Self::public_props();
+        let position = props
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, (index, _, _))| (Self::backing_of(*index), std::cmp::Reverse(*index)))
+            .map(|(position, _)| position)
+            .ok_or("No proposal to launch")?;
+
+        let (proposal_index, proposal, _) = props.remove(position);
+        PublicProps::<T>::put(props);
+        if let Some((depositors, deposit)) = DepositOf::<T>::remove(&proposal_index) {
+            for depositor in depositors {
+                <T as Config>::Currency::unreserve(&depositor, deposit);
+            }
+        }
+
+        ReferendumCount::<T>::put(next_index);
+        ReferendumInfoFor::<T>::insert(
+            ref_index,
+            ReferendumInfo::Ongoing {
+                end,
+                proposal,
+                tally: Tally {
+                    ayes: BalanceOf::<T>::zero(),
+                    nays: BalanceOf::<T>::zero(),
+                    turnout: BalanceOf::<T>::zero(),
+                },
+            },
+        );
+        let mut ending = ReferendumsEndingAt::<T>::get(&end).unwrap_or_default();
+        ending.push(ref_index);
+        ReferendumsEndingAt::<T>::insert(end, ending);
+        system::Pallet::<T>::deposit_event(Event::Started {
+            ref_index,
+            proposal_index,
+        });
+        Ok(ref_index)
+    }
+
+    /// Decide a referendum whose vote is over, and schedule its proposal if it passed.
+    fn bake_referendum(
+        now: T::BlockNumber,
+        ref_index: ReferendumIndex,
+        proposal: CallOf<T>,
+        tally: Tally<BalanceOf<T>>,
+    ) {
+        let approved = tally.ayes > tally.nays;
+        ReferendumInfoFor::<T>::insert(ref_index, ReferendumInfo::Finished { approved, end: now });
+        if !approved {
+            system::Pallet::<T>::deposit_event(Event::NotPassed { ref_index });
+            return;
+        }
+
+        system::Pallet::<T>::deposit_event(Event::Passed { ref_index });
+        let delay = T::EnactmentPeriod::get().max(T::BlockNumber::one());
+        // Anyone can fill the agenda of a block, so the proposal is enacted in the first block
+        // with room for it once the enactment period is over.
+        let scheduled = now
+            .checked_add(&delay)
+            .ok_or("Block number overflow")
+            .and_then(|earliest| {
+                scheduler::Pallet::<T>::do_schedule_earliest(
+                    T::RootAccount::get(),
+                    earliest,
+                    proposal,
+                )
+            });
+        if let Err(error) = scheduled {
+            system::Pallet::<T>::deposit_event(Event::EnactmentFailed { ref_index, error });
+        }
+    }
+}
+
+impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
+    /// Launch a referendum every `LaunchPeriod` blocks, and decide the referenda whose vote ends
+    /// in block `n`.
+    ///
+    /// Each referendum launched or decided weighs as much as an extrinsic.
+    fn on_initialize(n: T::BlockNumber) -> Weight {
+        let mut count: Weight = 0;
+        let last_launch = LastLaunch::<T>::get().unwrap_or_else(T::BlockNumber::zero);
+        let next_launch = last_launch.checked_add(&T::LaunchPeriod::get());
+        if next_launch.is_some_and(|next_launch| n >= next_launch) {
+            LastLaunch::<T>::put(n);
+            if Self::launch_public(n).is_ok() {
+                count += 1;
+            }
+        }
+
+        for ref_index in ReferendumsEndingAt::<T>::remove(&n).unwrap_or_default() {
+            if let Ok(ReferendumInfo::Ongoing {
+                proposal, tally, ..
+            }) = Self::referendum_info(ref_index)
+            {
+                Self::bake_referendum(n, ref_index, proposal, tally);
+                count += 1;
+            }
+        }
+        <T as system::Config>::ExtrinsicBaseWeight::get() * count
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        balances,
+        mock::{TestCall, TestConfig},
+        support::traits::{ConstU128, ConstU32},
+        types,
+    };
+
+    use super::*;
+
+    impl Config for TestConfig {
+        type Currency = balances::Pallet<TestConfig>;
+        type MinimumDeposit = ConstU128<10>;
+        type LaunchPeriod = ConstU32<2>;
+        type VotingPeriod = ConstU32<3>;
+        type EnactmentPeriod = ConstU32<2>;
+        type VoteLockingPeriod = ConstU32<2>;
+        type MaxProposals = ConstU32<2>;
+    }
+
+    type System = system::Pallet<TestConfig>;
+    type Balances = balances::Pallet<TestConfig>;
+    type Democracy = Pallet<TestConfig>;
+
+    fn run_to_block(n: types::BlockNumber) {
+        while System::block_number() < n {
+            System::inc_block_number();
+            let block = System::block_number();
+            scheduler::Pallet::<TestConfig>::on_initialize(block);
+            Democracy::on_initialize(block);
+        }
+    }
+
+    fn set_balance(who: &str, amount: u128) -> Box<TestCall> {
+        Box::new(TestCall::Balances(balances::Call::force_set_balance {
+            who: who.to_string(),
+            amount,
+        }))
+    }
+
+    fn vote(aye: bool, conviction: Conviction, balance: u128) -> AccountVote<u128> {
+        AccountVote {
+            aye,
+            conviction,
+            balance,
+        }
+    }
+
+    #[test]
+    fn passed_referendum_is_enacted_as_root() {
+        let (a, b) = ("A".to_string(), "B".to_string());
+        Balances::set_balance(&a, 100);
+        Balances::set_balance(&b, 100);
+        let mut democracy = Democracy::new();
+
+        assert_eq!(
+            democracy.propose(a.clone(), set_balance("C", 500), 5),
+            Err("Deposit is below the minimum")
+        );
+        democracy
+            .propose(a.clone(), set_balance("C", 1), 10)
+            .unwrap();
+        democracy
+            .propose(a.clone(), set_balance("C", 500), 10)
+            .unwrap();
+        democracy.second(b.clone(), 1).unwrap();
+        assert_eq!(Balances::reserved_balance(&a), 20);
+
+        // The seconded proposal is launched first, and its deposits are given back.
+        run_to_block(2);
+        assert_eq!(Democracy::public_props().len(), 1);
+        assert_eq!(Balances::reserved_balance(&a), 10);
+        assert_eq!(Balances::reserved_balance(&b), 0);
+
+        democracy
+            .vote(a.clone(), 0, vote(true, Conviction::Locked2x, 50))
+            .unwrap();
+        democracy
+            .vote(b.clone(), 0, vote(false, Conviction::None, 100))
+            .unwrap();
+        run_to_block(5);
+        assert_eq!(
+            Democracy::referendum_info(0),
+            Ok(ReferendumInfo::Finished {
+                approved: true,
+                end: 5
+            })
+        );
+        assert_eq!(
+            democracy.vote(b.clone(), 0, vote(false, Conviction::None, 100)),
+            Err("Referendum is not ongoing")
+        );
+
+        run_to_block(6);
+        assert_eq!(Balances::balance(&"C".to_string()), 0);
+        run_to_block(7);
+        assert_eq!(Balances::balance(&"C".to_string()), 500);
+    }
+
+    #[test]
+    fn enactment_moves_past_a_full_agenda() {
+        let (a, d) = ("A".to_string(), "D".to_string());
+        Balances::set_balance(&a, 100);
+        Balances::set_balance(&d, 100);
+        let mut democracy = Democracy::new();
+        democracy
+            .propose(a.clone(), set_balance("C", 500), 10)
+            .unwrap();
+        run_to_block(2);
+        democracy
+            .vote(a, 0, vote(true, Conviction::Locked1x, 50))
+            .unwrap();
+
+        // D fills the agenda of the block the referendum would be enacted in.
+        let mut scheduler = scheduler::Pallet::<TestConfig>::new();
+        for _ in 0..2 {
+            scheduler
+                .schedule(d.clone(), 7, None, set_balance("D", 100))
+                .unwrap();
+        }
+        run_to_block(7);
+        assert_eq!(Balances::balance(&"C".to_string()), 0);
+        run_to_block(8);
+        assert_eq!(Balances::balance(&"C".to_string()), 500);
+    }
+
+    #[test]
+    fn conviction_locks_the_vote_after_the_referendum() {
+        let (a, b) = ("A".to_string(), "B".to_string());
+        Balances::set_balance(&a, 100);
+        Balances::set_balance(&b, 100);
+        let mut democracy = Democracy::new();
+        democracy
+            .propose(a.clone(), set_balance("C", 500), 10)
+            .unwrap();
+        run_to_block(2);
+
+        assert_eq!(
+            democracy.vote(a.clone(), 0, vote(true, Conviction::None, 101)),
+            Err("Insufficient free balance to vote")
+        );
+        democracy
+            .vote(a.clone(), 0, vote(true, Conviction::Locked1x, 10))
+            .unwrap();
+        democracy
+            .vote(b.clone(), 0, vote(true, Conviction::Locked2x, 60))
+            .unwrap();
+        // Voting again replaces the previous vote.
+        democracy
+            .vote(a.clone(), 0, vote(false, Conviction::Locked1x, 80))
+            .unwrap();
+        run_to_block(5);
+        assert_eq!(
+            Democracy::referendum_info(0),
+            Ok(ReferendumInfo::Finished {
+                approved: true,
+                end: 5
+            })
+        );
+
+        // A is locked for 2 blocks after the end of the referendum, B for 4.
+        run_to_block(6);
+        democracy.unlock(b.clone(), a.clone()).unwrap();
+        assert_eq!(Balances::frozen_balance(&a, WithdrawReasons::Transfer), 80);
+        run_to_block(7);
+        democracy.unlock(b.clone(), a.clone()).unwrap();
+        assert!(Balances::locks(&a).is_empty());
+        assert!(Democracy::votes_of(&a).is_empty());
+        democracy.unlock(b.clone(), b.clone()).unwrap();
+        assert_eq!(Balances::frozen_balance(&b, WithdrawReasons::Transfer), 60);
+        run_to_block(9);
+        democracy.unlock(a, b.clone()).unwrap();
+        assert!(Balances::locks(&b).is_empty());
+    }
+}
@@ -0,0 +1,567 @@
+use std::{fmt::Debug, marker::PhantomData};
+
+use num::traits::{Saturating, SaturatingMul, Unsigned, Zero};
+
+use crate::{
+    support::{
+        codec::Encode,
+        storage::{StorageMap, StorageValue},
+        traits::{Currency, EnsureOrigin, Get, Hooks, OnUnbalanced, ReservableCurrency},
+        DispatchResult,
+    },
+    system,
+};
+
+pub type RegistrarIndex = u32;
+
+pub type BalanceOf<T> =
+    <<T as Config>::Currency as Currency<<T as system::Config>::AccountId>>::Balance;
+
+pub type NegativeImbalanceOf<T> =
+    <<T as Config>::Currency as Currency<<T as system::Config>::AccountId>>::NegativeImbalance;
+
+pub trait Config: system::Config<RuntimeEvent: From<Event<Self>>> {
+    /// The currency deposits are reserved in.
+    type Currency: ReservableCurrency<
+        Self::AccountId,
+        Balance: Unsigned + Saturating + SaturatingMul + From<u32> + Copy + Ord + Debug,
+    >;
+    /// The deposit of an identity.
+    type BasicDeposit: Get<BalanceOf<Self>>;
+    /// The deposit of each additional field of an identity, on top of `BasicDeposit`.
+    type FieldDeposit: Get<BalanceOf<Self>>;
+    /// The deposit of each sub-account.
+    type SubAccountDeposit: Get<BalanceOf<Self>>;
+    /// The longest a field, or the name of a sub-account, can be, in bytes.
+    type StringLimit: Get<u32>;
+    type MaxAdditionalFields: Get<u32>;
+    type MaxSubAccounts: Get<u32>;
+    type MaxRegistrars: Get<u32>;
+    /// Who can add registrars.
+    type RegistrarOrigin: EnsureOrigin<Self::AccountId>;
+    /// Who can remove an identity, slashing its deposit.
+    type ForceOrigin: EnsureOrigin<Self::AccountId>;
+    /// Where the slashed deposits of removed identities go, usually the treasury.
+    type Slashed: OnUnbalanced<NegativeImbalanceOf<Self>>;
+}
+
+/// What an account says about itself.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct IdentityInfo {
+    pub display: Option<Vec<u8>>,
+    pub email: Option<Vec<u8>>,
+    pub web: Option<Vec<u8>>,
+    /// Any other fields, as key and value pairs.
+    pub additional: Vec<(Vec<u8>, Vec<u8>)>,
+}
+
+impl Encode for IdentityInfo {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.display.encode_to(dest);
+        self.email.encode_to(dest);
+        self.web.encode_to(dest);
+        self.additional.encode_to(dest);
+    }
+}
+
+impl IdentityInfo {
+    fn fields(&self) -> impl Iterator<Item = &Vec<u8>> {
+        [&self.display, &self.email, &self.web]
+            .into_iter()
+            .flatten()
+            .chain(self.additional.iter().flat_map(|(key, value)| [key, value]))
+    }
+}
+
+/// The opinion of a registrar on the identity of an account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Judgement {
+    Unknown,
+    Reasonable,
+    KnownGood,
+    OutOfDate,
+    LowQuality,
+    /// The identity is wrong. Unlike other judgements, it stays when the identity changes.
+    Erroneous,
+}
+
+impl Encode for Judgement {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        dest.push(*self as u8);
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Registration<Balance> {
+    /// The judgements of the registrars, sorted by registrar index.
+    pub judgements: Vec<(RegistrarIndex, Judgement)>,
+    /// The deposit reserved for the identity.
+    pub deposit: Balance,
+    pub info: IdentityInfo,
+}
+
+pub type RegistrationOf<T> = Registration<BalanceOf<T>>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event<T: Config> {
+    IdentitySet {
+        who: T::AccountId,
+    },
+    /// An identity and its sub-accounts were cleared, and `deposit` was returned.
+    IdentityCleared {
+        who: T::AccountId,
+        deposit: BalanceOf<T>,
+    },
+    /// An identity and its sub-accounts were removed by `ForceOrigin`, and `deposit` was slashed.
+    IdentityKilled {
+        who: T::AccountId,
+        deposit: BalanceOf<T>,
+    },
+    RegistrarAdded {
+        registrar_index: RegistrarIndex,
+    },
+    JudgementGiven {
+        target: T::AccountId,
+        registrar_index: RegistrarIndex,
+    },
+    SubIdentitiesSet {
+        main: T::AccountId,
+        deposit: BalanceOf<T>,
+    },
+    SubIdentityRevoked {
+        sub: T::AccountId,
+        main: T::AccountId,
+    },
+}
+
+pub struct IdentityOf<T>(PhantomData<T>);
+impl<T: Config> StorageMap for IdentityOf<T> {
+    type Key = T::AccountId;
+    type Value = RegistrationOf<T>;
+}
+
+/// The main account of each sub-account, and the name of the sub-account.
+pub struct SuperOf<T>(PhantomData<T>);
+impl<T: Config> StorageMap for SuperOf<T> {
+    type Key = T::AccountId;
+    type Value = (T::AccountId, Vec<u8>);
+}
+
+/// The deposit reserved for the sub-accounts of each account, and the sub-accounts.
+pub struct SubsOf<T>(PhantomData<T>);
+impl<T: Config> StorageMap for SubsOf<T> {
+    type Key = T::AccountId;
+    type Value = (BalanceOf<T>, Vec<T::AccountId>);
+}
+
+/// The registrars, indexed by their position.
+pub struct Registrars<T>(PhantomData<T>);
+impl<T: Config> StorageValue for Registrars<T> {
+    type Value = Vec<T::AccountId>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pallet<T: Config>(PhantomData<T>);
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+    /// Set the identity of the caller, reserving `BasicDeposit` plus `FieldDeposit` per
+    /// additional field.
+    ///
+    /// Judgements on a previous identity are removed, except `Judgement::Erroneous` ones.
+    pub fn set_identity(&mut self, caller: T::AccountId, info: IdentityInfo) -> DispatchResult {
+        if info.additional.len() > T::MaxAdditionalFields::get() as usize {
+            return Err("Too many additional fields");
+        }
+        let limit = T::StringLimit::get() as usize;
+        if info.fields().any(|field| field.len() > limit) {
+            return Err("Field is too long");
+        }
+
+        let deposit = T::BasicDeposit::get().saturating_add(
+            T::FieldDeposit::get().saturating_mul(&(info.additional.len() as u32).into()),
+        );
+        let (old_deposit, mut judgements) = match IdentityOf::<T>::get(&caller) {
+            Some(registration) => (registration.deposit, registration.judgements),
+            None => (BalanceOf::<T>::zero(), Vec::new()),
+        };
+        Self::update_deposit(&caller, old_deposit, deposit)?;
+        judgements.retain(|(_, judgement)| *judgement == Judgement::Erroneous);
+
+        IdentityOf::<T>::insert(
+            caller.clone(),
+            Registration {
+                judgements,
+                deposit,
+                info,
+            },
+        );
+        system::Pallet::<T>::deposit_event(Event::IdentitySet { who: caller });
+        Ok(())
+    }
+
+    /// Clear the identity and the sub-accounts of the caller, returning their deposits.
+    pub fn clear_identity(&mut self, caller: T::AccountId) -> DispatchResult {
+        let deposit = Self::remove_identity(&caller)?;
+        T::Currency::unreserve(&caller, deposit);
+        system::Pallet::<T>::deposit_event(Event::IdentityCleared {
+            who: caller,
+            deposit,
+        });
+        Ok(())
+    }
+
+    /// Remove the identity and the sub-accounts of `target`, slashing their deposits to
+    /// `Slashed`. Only `ForceOrigin` can call it.
+    pub fn kill_identity(&mut self, caller: T::AccountId, target: T::AccountId) -> DispatchResult {
+        T::ForceOrigin::ensure_origin(&caller)?;
+        let deposit = Self::remove_identity(&target)?;
+        let (slashed, _) = T::Currency::slash_reserved(&target, deposit);
+        T::Slashed::on_unbalanced(slashed);
+        system::Pallet::<T>::deposit_event(Event::IdentityKilled {
+            who: target,
+            deposit,
+        });
+        Ok(())
+    }
+
+    /// Add `account` as a registrar. Only `RegistrarOrigin` can call it.
+    pub fn add_registrar(&mut self, caller: T::AccountId, account: T::AccountId) -> DispatchResult {
+        T::RegistrarOrigin::ensure_origin(&caller)?;
+        let mut registrars = Self::registrars();
+        if registrars.len() >= T::MaxRegistrars::get() as usize {
+            return Err("Too many registrars");
+        }
+        let registrar_index = registrars.len() as RegistrarIndex;
+        registrars.push(account);
+        Registrars::<T>::put(registrars);
+        system::Pallet::<T>::deposit_event(Event::RegistrarAdded { registrar_index });
+        Ok(())
+    }
+
+    /// Give the judgement of the registrar at `registrar_index`, which must be the caller, on the
+    /// identity of `target`, replacing its previous judgement.
+    pub fn provide_judgement(
+        &mut self,
+        caller: T::AccountId,
+        registrar_index: RegistrarIndex,
+        target: T::AccountId,
+        judgement: Judgement,
+    ) -> DispatchResult {
+        if Self::registrars().get(registrar_index as usize) != Some(&caller) {
+            return Err("Caller is not the registrar");
+        }
+        let mut registration = IdentityOf::<T>::get(&target).ok_or("Account has no identity")?;
+        match registration
+            .judgements
+            .binary_search_by_key(&registrar_index, |(index, _)| *index)
+        {
+            Ok(position) => registration.judgements[position].1 = judgement,
+            Err(position) => registration
+                .judgements
+                .insert(position, (registrar_index, judgement)),
+        }
+
+        IdentityOf::<T>::insert(target.clone(), registration);
+        system::Pallet::<T>::deposit_event(Event::JudgementGiven {
+            target,
+            registrar_index,
+        });
+        Ok(())
+    }
+
+    /// Replace the sub-accounts of the caller, each with its name, reserving `SubAccountDeposit`
+    /// per sub-account. The caller must have an identity.
+    pub fn set_subs(
+        &mut self,
+        caller: T::AccountId,
+        subs: Vec<(T::AccountId, Vec<u8>)>,
+    ) -> DispatchResult {
+        if !IdentityOf::<T>::contains_key(&caller) {
+            return Err("Account has no identity");
+        }
+        if subs.len() > T::MaxSubAccounts::get() as usize {
+            return Err("Too many sub-accounts");
+        }
+        let limit = T::StringLimit::get() as usize;
+        for (sub, name) in &subs {
+            if name.len() > limit {
+                return Err("Field is too long");
+            }
+            if *sub == caller
+                || SuperOf::<T>::get(sub).is_some_and(|(main, _)| main != caller)
+                || subs.iter().filter(|(other, _)| other == sub).count() > 1
+            {
+                return Err("Account cannot be a sub-account");
+            }
+        }
+
+        let deposit = T::SubAccountDeposit::get().saturating_mul(&(subs.len() as u32).into());
+        let (old_deposit, old_subs) =
+            SubsOf::<T>::get(&caller).unwrap_or((BalanceOf::<T>::zero(), Vec::new()));
+        Self::update_deposit(&caller, old_deposit, deposit)?;
+
+        for sub in old_subs {
+            SuperOf::<T>::remove(&sub);
+        }
+        let mut ids = Vec::with_capacity(subs.len());
+        for (sub, name) in subs {
+            SuperOf::<T>::insert(sub.clone(), (caller.clone(), name));
+            ids.push(sub);
+        }
+        if ids.is_empty() {
+            SubsOf::<T>::remove(&caller);
+        } else {
+            SubsOf::<T>::insert(caller.clone(), (deposit, ids));
+        }
+        system::Pallet::<T>::deposit_event(Event::SubIdentitiesSet {
+            main: caller,
+            deposit,
+        });
+        Ok(())
+    }
+
+    /// Unlink the caller from its main account, which gets the deposit for it back.
+    pub fn quit_sub(&mut self, caller: T::AccountId) -> DispatchResult {
+        let (main, _) = SuperOf::<T>::remove(&caller).ok_or("Account is not a sub-account")?;
+        SubsOf::<T>::mutate(&main, |subs_of| {
+            if let Some((deposit, subs)) = subs_of {
+                subs.retain(|sub| *sub != caller);
+                // The last sub-account to quit releases whatever deposit is left, so none stays
+                // reserved without a `SubsOf` entry accounting for it.
+                let refund = if subs.is_empty() {
+                    *deposit
+                } else {
+                    T::SubAccountDeposit::get().min(*deposit)
+                };
+                *deposit = deposit.saturating_sub(refund);
+                T::Currency::unreserve(&main, refund);
+                if subs.is_empty() {
+                    *subs_of = None;
+                }
+            }
+        });
+        system::Pallet::<T>::deposit_event(Event::SubIdentityRevoked { sub: caller, main });
+        Ok(())
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn new() -> Self {
+        Pallet(PhantomData)
+    }
+
+    pub fn identity(who: &T::AccountId) -> Option<RegistrationOf<T>> {
+        IdentityOf::<T>::get(who)
+    }
+
+    /// The main account of `who` and the name of `who` under it, if `who` is a sub-account.
+    pub fn super_of(who: &T::AccountId) -> Option<(T::AccountId, Vec<u8>)> {
+        SuperOf::<T>::get(who)
+    }
+
+    pub fn subs_of(who: &T::AccountId) -> Vec<T::AccountId> {
+        SubsOf::<T>::get(who)
+            .map(|(_, subs)| subs)
+            .unwrap_or_default()
+    }
+
+    pub fn registrars() -> Vec<T::AccountId> {
+        Registrars::<T>::get().unwrap_or_default()
+    }
+
+    /// A human-readable name for `who`: the display name of its identity, or for a sub-account,
+    /// the display name of its main account followed by its own name.
+    pub fn display_name(who: &T::AccountId) -> Option<String> {
+        let display = |who| {
+            IdentityOf::<T>::get(who)
+                .and_then(|registration| registration.info.display)
+                .map(|display| String::from_utf8_lossy(&display).into_owned())
+        };
+        match SuperOf::<T>::get(who) {
+            Some((main, name)) => Some(format!(
+                "{}/{}",
+                display(&main)?,
+                String::from_utf8_lossy(&name)
+            )),
+            None => display(who),
+        }
+    }
+
+    /// Remove the identity and the sub-accounts of `who`, returning their deposits, which are
+    /// still reserved.
+    fn remove_identity(who: &T::AccountId) -> Result<BalanceOf<T>, &'static str> {
+        let registration = IdentityOf::<T>::remove(who).ok_or("Account has no identity")?;
+        let (subs_deposit, subs) =
+            SubsOf::<T>::remove(who).unwrap_or((BalanceOf::<T>::zero(), Vec::new()));
+        for sub in subs {
+            SuperOf::<T>::remove(&sub);
+        }
+        Ok(registration.deposit.saturating_add(subs_deposit))
+    }
+
+    /// Reserve or unreserve the difference between the `old` and `new` deposits of `who`.
+    fn update_deposit(who: &T::AccountId, old: BalanceOf<T>, new: BalanceOf<T>) -> DispatchResult {
+        if new > old {
+            T::Currency::reserve(who, new - old)?;
+        } else {
+            T::Currency::unreserve(who, old - new);
+        }
+        Ok(())
+    }
+}
+
+impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        balances,
+        mock::TestConfig,
+        support::traits::{ConstU128, ConstU32},
+        types,
+    };
+
+    use super::*;
+
+    impl Config for TestConfig {
+        type Currency = balances::Pallet<TestConfig>;
+        type BasicDeposit = ConstU128<10>;
+        type FieldDeposit = ConstU128<2>;
+        type SubAccountDeposit = ConstU128<5>;
+        type StringLimit = ConstU32<8>;
+        type MaxAdditionalFields = ConstU32<2>;
+        type MaxSubAccounts = ConstU32<2>;
+        type MaxRegistrars = ConstU32<1>;
+        type RegistrarOrigin = system::EnsureRoot<TestConfig>;
+        type ForceOrigin = system::EnsureRoot<TestConfig>;
+        type Slashed = ();
+    }
+
+    type Balances = balances::Pallet<TestConfig>;
+    type Identity = Pallet<TestConfig>;
+
+    fn info(display: &str, additional: usize) -> IdentityInfo {
+        IdentityInfo {
+            display: Some(display.as_bytes().to_vec()),
+            additional: vec![(b"key".to_vec(), b"value".to_vec()); additional],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn registrars_judge_identities() {
+        let (a, r, root) = ("A".to_string(), "R".to_string(), types::RootAccount::get());
+        Balances::set_balance(&a, 100);
+        let mut identity = Identity::new();
+
+        assert_eq!(
+            identity.set_identity(a.clone(), info("Alice", 3)),
+            Err("Too many additional fields")
+        );
+        assert_eq!(
+            identity.set_identity(a.clone(), info("Alice Liddell", 0)),
+            Err("Field is too long")
+        );
+        identity.set_identity(a.clone(), info("Alice", 2)).unwrap();
+        assert_eq!(Balances::reserved_balance(&a), 14);
+        assert_eq!(Identity::display_name(&a), Some("Alice".to_string()));
+
+        assert_eq!(
+            identity.add_registrar(a.clone(), r.clone()),
+            Err("Caller is not root")
+        );
+        identity.add_registrar(root.clone(), r.clone()).unwrap();
+        assert_eq!(
+            identity.add_registrar(root, a.clone()),
+            Err("Too many registrars")
+        );
+        assert_eq!(
+            identity.provide_judgement(a.clone(), 0, a.clone(), Judgement::KnownGood),
+            Err("Caller is not the registrar")
+        );
+        identity
+            .provide_judgement(r.clone(), 0, a.clone(), Judgement::KnownGood)
+            .unwrap();
+        assert_eq!(
+            Identity::identity(&a).unwrap().judgements,
+            vec![(0, Judgement::KnownGood)]
+        );
+
+        // A new identity needs a new judgement, and its deposit is adjusted.
+        identity.set_identity(a.clone(), info("Alice", 0)).unwrap();
+        assert_eq!(Balances::reserved_balance(&a), 10);
+        assert!(Identity::identity(&a).unwrap().judgements.is_empty());
+
+        identity.clear_identity(a.clone()).unwrap();
+        assert_eq!(Balances::reserved_balance(&a), 0);
+        assert_eq!(Identity::display_name(&a), None);
+    }
+
+    #[test]
+    fn sub_accounts_are_named_after_their_main_account() {
+        let (a, b, c) = ("A".to_string(), "B".to_string(), "C".to_string());
+        Balances::set_balance(&a, 100);
+        Balances::set_balance(&b, 100);
+        let mut identity = Identity::new();
+
+        assert_eq!(
+            identity.set_subs(a.clone(), vec![(b.clone(), b"bob".to_vec())]),
+            Err("Account has no identity")
+        );
+        identity.set_identity(a.clone(), info("Alice", 0)).unwrap();
+        identity.set_identity(b.clone(), info("Bob", 0)).unwrap();
+        identity
+            .set_subs(
+                a.clone(),
+                vec![(b.clone(), b"bob".to_vec()), (c.clone(), b"carol".to_vec())],
+            )
+            .unwrap();
+        assert_eq!(Balances::reserved_balance(&a), 10 + 10);
+        assert_eq!(Identity::display_name(&c), Some("Alice/carol".to_string()));
+        assert_eq!(
+            identity.set_subs(b.clone(), vec![(c.clone(), b"carol".to_vec())]),
+            Err("Account cannot be a sub-account")
+        );
+
+        identity.quit_sub(c.clone()).unwrap();
+        assert_eq!(Identity::subs_of(&a), vec![b.clone()]);
+        assert_eq!(Balances::reserved_balance(&a), 10 + 5);
+        assert_eq!(Identity::display_name(&c), None);
+
+        // The last sub-account to quit leaves no entry behind.
+        identity.quit_sub(b.clone()).unwrap();
+        assert_eq!(SubsOf::<TestConfig>::get(&a), None);
+        assert_eq!(Balances::reserved_balance(&a), 10);
+        identity
+            .set_subs(a.clone(), vec![(b.clone(), b"bob".to_vec())])
+            .unwrap();
+
+        identity.clear_identity(a.clone()).unwrap();
+        assert_eq!(Balances::reserved_balance(&a), 0);
+        assert_eq!(Identity::super_of(&b), None);
+        assert_eq!(Identity::display_name(&b), Some("Bob".to_string()));
+    }
+
+    #[test]
+    fn last_sub_to_quit_releases_the_whole_deposit() {
+        let (a, b) = ("A".to_string(), "B".to_string());
+        Balances::set_balance(&a, 100);
+        let mut identity = Identity::new();
+        identity.set_identity(a.clone(), info("Alice", 0)).unwrap();
+        identity
+            .set_subs(a.clone(), vec![(b.clone(), b"bob".to_vec())])
+            .unwrap();
+        // A deposit taken when sub-accounts cost more than they do now.
+        Balances::reserve(&a, 3).unwrap();
+        SubsOf::<TestConfig>::mutate(&a, |subs_of| {
+            if let Some((deposit, _)) = subs_of {
+                *deposit += 3;
+            }
+        });
+
+        identity.quit_sub(b.clone()).unwrap();
+        assert_eq!(SubsOf::<TestConfig>::get(&a), None);
+        assert_eq!(Balances::reserved_balance(&a), 10);
+    }
+}
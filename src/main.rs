@@ -1,40 +1,200 @@
+mod assets;
 mod balances;
+mod cli;
+mod collective;
+mod democracy;
+mod identity;
+#[cfg(test)]
+mod mock;
+mod multisig;
+mod nfts;
 mod proof_of_existence;
+mod proxy;
+mod randomness;
+mod scheduler;
+mod staking;
 mod support;
 mod system;
+mod timestamp;
+mod treasury;
 mod types;
+mod utility;
+mod vesting;
 
 use support::Dispatch;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[macros::runtime]
 pub struct Runtime {
     pub system: system::Pallet<Runtime>,
+    pub timestamp: timestamp::Pallet<Runtime>,
+    #[no_call]
+    #[no_event]
+    pub randomness: randomness::Pallet<Runtime>,
     pub balances: balances::Pallet<Runtime>,
+    pub test_balances: balances::Pallet<Runtime, support::instances::Instance1>,
     pub proof_of_existence: proof_of_existence::Pallet<Runtime>,
+    pub utility: utility::Pallet<Runtime>,
+    pub multisig: multisig::Pallet<Runtime>,
+    pub proxy: proxy::Pallet<Runtime>,
+    pub scheduler: scheduler::Pallet<Runtime>,
+    pub vesting: vesting::Pallet<Runtime>,
+    pub assets: assets::Pallet<Runtime>,
+    pub nfts: nfts::Pallet<Runtime>,
+    pub staking: staking::Pallet<Runtime>,
+    pub democracy: democracy::Pallet<Runtime>,
+    pub collective: collective::Pallet<Runtime>,
+    pub treasury: treasury::Pallet<Runtime>,
+    pub identity: identity::Pallet<Runtime>,
 }
 
 impl system::Config for Runtime {
     type AccountId = types::AccountId;
     type BlockNumber = types::BlockNumber;
     type Nonce = types::Nonce;
+    type RuntimeEvent = RuntimeEvent;
+    type MaximumBlockWeight = support::traits::ConstU64<1_000>;
+    type ExtrinsicBaseWeight = support::traits::ConstU64<10>;
+    type RootAccount = types::RootAccount;
 }
 
+impl timestamp::Config for Runtime {
+    type Moment = types::Moment;
+    type MinimumPeriod = support::traits::ConstU64<3_000>;
+}
+
+impl randomness::Config for Runtime {}
+
 impl balances::Config for Runtime {
     type Balance = types::Balance;
 }
 
+/// A second token, kept apart from the native one.
+impl balances::Config<support::instances::Instance1> for Runtime {
+    type Balance = types::Balance;
+}
+
 impl proof_of_existence::Config for Runtime {
     type Content = types::Content;
+    type Currency = balances::Pallet<Runtime>;
+    type ClaimDeposit = support::traits::ConstU128<10>;
+}
+
+impl utility::Config for Runtime {
+    type RuntimeCall = RuntimeCall;
+}
+
+impl multisig::Config for Runtime {
+    type RuntimeCall = RuntimeCall;
+    type AccountIdFromHash = types::HexAccountId;
+    type MaxSignatories = support::traits::ConstU32<10>;
+}
+
+impl proxy::Config for Runtime {
+    type RuntimeCall = RuntimeCall;
+    type ProxyType = types::ProxyType;
+    type MaxProxies = support::traits::ConstU32<32>;
+    type MaxPending = support::traits::ConstU32<32>;
+}
+
+impl scheduler::Config for Runtime {
+    type RuntimeCall = RuntimeCall;
+    type Currency = balances::Pallet<Runtime>;
+    type ScheduleDeposit = support::traits::ConstU128<10>;
+    type MaxScheduledPerBlock = support::traits::ConstU32<50>;
+}
+
+impl vesting::Config for Runtime {
+    type Currency = balances::Pallet<Runtime>;
+    type BlockNumberToBalance = support::traits::ConvertInto;
+    type MinVestedTransfer = support::traits::ConstU128<10>;
+    type MaxVestingSchedules = support::traits::ConstU32<28>;
+}
+
+impl assets::Config for Runtime {
+    type AssetId = types::AssetId;
+    type StringLimit = support::traits::ConstU32<50>;
+}
+
+impl nfts::Config for Runtime {
+    type CollectionId = types::CollectionId;
+    type ItemId = types::ItemId;
+    type KeyLimit = support::traits::ConstU32<32>;
+    type ValueLimit = support::traits::ConstU32<256>;
+}
+
+impl staking::Config for Runtime {
+    type Currency = balances::Pallet<Runtime>;
+    type EraLength = support::traits::ConstU32<10>;
+    type ValidatorCount = support::traits::ConstU32<4>;
+    type BondingDuration = support::traits::ConstU32<3>;
+    type RewardPerEra = support::traits::ConstU128<1_000>;
+    type MaxNominations = support::traits::ConstU32<16>;
+}
+
+impl democracy::Config for Runtime {
+    type Currency = balances::Pallet<Runtime>;
+    type MinimumDeposit = support::traits::ConstU128<10>;
+    type LaunchPeriod = support::traits::ConstU32<10>;
+    type VotingPeriod = support::traits::ConstU32<10>;
+    type EnactmentPeriod = support::traits::ConstU32<5>;
+    type VoteLockingPeriod = support::traits::ConstU32<10>;
+    type MaxProposals = support::traits::ConstU32<100>;
+}
+
+impl collective::Config for Runtime {
+    type RuntimeCall = RuntimeCall;
+    type AccountIdFromHash = types::HexAccountId;
+    type MotionDuration = support::traits::ConstU32<10>;
+    type MaxProposals = support::traits::ConstU32<100>;
+    type MaxMembers = support::traits::ConstU32<100>;
+}
+
+impl treasury::Config for Runtime {
+    type Currency = balances::Pallet<Runtime>;
+    type AccountIdFromHash = types::HexAccountId;
+    type ApproveOrigin = collective::EnsureMembers<Runtime, 2>;
+    type RejectOrigin = collective::EnsureMembers<Runtime, 2>;
+    type ProposalBond = support::traits::ConstU32<5>;
+    type ProposalBondMinimum = support::traits::ConstU128<10>;
+    type SpendPeriod = support::traits::ConstU32<20>;
+    type Burn = support::traits::ConstU32<1>;
+    type MaxApprovals = support::traits::ConstU32<100>;
+}
+
+impl identity::Config for Runtime {
+    type Currency = balances::Pallet<Runtime>;
+    type BasicDeposit = support::traits::ConstU128<10>;
+    type FieldDeposit = support::traits::ConstU128<2>;
+    type SubAccountDeposit = support::traits::ConstU128<5>;
+    type StringLimit = support::traits::ConstU32<64>;
+    type MaxAdditionalFields = support::traits::ConstU32<16>;
+    type MaxSubAccounts = support::traits::ConstU32<16>;
+    type MaxRegistrars = support::traits::ConstU32<20>;
+    type RegistrarOrigin = system::EnsureRoot<Runtime>;
+    type ForceOrigin = collective::EnsureMembers<Runtime, 2>;
+    type Slashed = treasury::Pallet<Runtime>;
 }
 
 fn main() {
     let a: String = String::from("A");
     let b: String = String::from("B");
     let mut runtime = Runtime::new();
-    runtime.balances.set_balance(&a, 100);
+    balances::Pallet::<Runtime>::set_balance(&a, 100);
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        balances::Pallet::<Runtime>::set_balance(&b, 100);
+        if let Err(e) = cli::run(&mut runtime, &args) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
+
     let block = support::Block {
         header: support::Header { block_number: 1 },
+        inherents: vec![RuntimeCall::timestamp(timestamp::Call::set { now: 6_000 })],
         extrinsics: vec![
             support::Extrinsic {
                 caller: a.clone(),
@@ -54,24 +214,125 @@ fn main() {
     };
 
     runtime.execute_block(block).expect("Cannot execute block");
+    println!("{:#?}", system::Pallet::<Runtime>::events());
 
     let block1 = support::Block {
         header: support::Header { block_number: 2 },
+        inherents: vec![RuntimeCall::timestamp(timestamp::Call::set { now: 12_000 })],
         extrinsics: vec![
             support::Extrinsic {
                 caller: a.clone(),
                 call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
-                    claim: "transactionA",
+                    claim: proof_of_existence::Pallet::<Runtime>::hash_of(
+                        &b"transactionA".to_vec(),
+                    ),
+                    expires_at: None,
                 }),
             },
             support::Extrinsic {
                 caller: b.clone(),
                 call: RuntimeCall::proof_of_existence(proof_of_existence::Call::create_claim {
-                    claim: "transactionB",
+                    claim: proof_of_existence::Pallet::<Runtime>::hash_of(
+                        &b"transactionB".to_vec(),
+                    ),
+                    expires_at: None,
                 }),
             },
         ],
     };
 
-    runtime.execute_block(block1).expect("Cannot execute block")
+    runtime.execute_block(block1).expect("Cannot execute block");
+    println!("{:#?}", system::Pallet::<Runtime>::events());
+
+    // B pays two accounts at once. The second transfer fails, so neither is kept.
+    let block2 = support::Block {
+        header: support::Header { block_number: 3 },
+        inherents: vec![RuntimeCall::timestamp(timestamp::Call::set { now: 18_000 })],
+        extrinsics: vec![support::Extrinsic {
+            caller: b.clone(),
+            call: RuntimeCall::utility(utility::Call::batch_all {
+                calls: vec![
+                    RuntimeCall::balances(balances::Call::transfer {
+                        to: a.clone(),
+                        amount: 50,
+                    }),
+                    RuntimeCall::balances(balances::Call::transfer {
+                        to: String::from("C"),
+                        amount: 100,
+                    }),
+                ],
+            }),
+        }],
+    };
+
+    runtime.execute_block(block2).expect("Cannot execute block");
+    println!("{:#?}", system::Pallet::<Runtime>::events());
+}
+
+#[cfg(test)]
+mod test {
+    use support::traits::ReservableCurrency;
+
+    use super::*;
+
+    fn block(block_number: types::BlockNumber, inherents: Vec<RuntimeCall>) -> types::Block {
+        support::Block {
+            header: support::Header { block_number },
+            inherents,
+            extrinsics: vec![support::Extrinsic {
+                caller: String::from("A"),
+                call: RuntimeCall::balances(balances::Call::transfer {
+                    to: String::from("B"),
+                    amount: 50,
+                }),
+            }],
+        }
+    }
+
+    #[test]
+    fn invalid_block_leaves_no_changes() {
+        let mut runtime = Runtime::new();
+        balances::Pallet::<Runtime>::set_balance(&String::from("A"), 100);
+
+        let invalid_timestamp = block(
+            1,
+            vec![RuntimeCall::timestamp(timestamp::Call::set { now: 1 })],
+        );
+        assert_eq!(
+            runtime.execute_block(invalid_timestamp),
+            Err("Timestamp must increment by at least MinimumPeriod between blocks")
+        );
+        assert_eq!(
+            runtime.execute_block(block(1, Vec::new())),
+            Err("Timestamp must be set once in the block")
+        );
+        assert_eq!(system::Pallet::<Runtime>::block_number(), 0);
+        assert_eq!(balances::Pallet::<Runtime>::balance(&String::from("B")), 0);
+
+        let valid = block(
+            1,
+            vec![RuntimeCall::timestamp(timestamp::Call::set { now: 6_000 })],
+        );
+        runtime.execute_block(valid).unwrap();
+        assert_eq!(system::Pallet::<Runtime>::block_number(), 1);
+        assert_eq!(balances::Pallet::<Runtime>::balance(&String::from("B")), 50);
+    }
+
+    #[test]
+    fn slashed_deposits_go_to_the_treasury() {
+        let a = String::from("A");
+        balances::Pallet::<Runtime>::set_balance(&a, 100);
+        let mut identity = identity::Pallet::<Runtime>::new();
+        identity
+            .set_identity(a.clone(), identity::IdentityInfo::default())
+            .unwrap();
+
+        // The council removes the identity, whose deposit is slashed.
+        identity
+            .kill_identity(collective::Pallet::<Runtime>::account_id(2), a.clone())
+            .unwrap();
+        assert_eq!(balances::Pallet::<Runtime>::balance(&a), 90);
+        assert_eq!(balances::Pallet::<Runtime>::reserved_balance(&a), 0);
+        assert_eq!(treasury::Pallet::<Runtime>::pot(), 10);
+    }
 }
@@ -0,0 +1,177 @@
+//! A runtime shared by the tests of the pallets.
+//!
+//! It wires `TestConfig` into the system and balances pallets and aggregates the events and calls
+//! the tests need. Each pallet implements its own `Config` for `TestConfig` in its test module.
+
+use crate::{
+    assets, balances, collective, democracy, identity, multisig, nfts, proxy, scheduler, staking,
+    support::{
+        codec::Encode, instances::Instance1, traits::ConstU64, Dispatch, DispatchResult,
+        Dispatchable,
+    },
+    system, timestamp, treasury, types, vesting,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestConfig;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TestEvent {
+    Balances(balances::Event<TestConfig>),
+    BalancesInstance1(balances::Event<TestConfig, Instance1>),
+    Multisig(multisig::Event<TestConfig>),
+    Proxy(proxy::Event<TestConfig>),
+    Scheduler(scheduler::Event<TestConfig>),
+    Timestamp(timestamp::Event<TestConfig>),
+    Vesting(vesting::Event<TestConfig>),
+    Assets(assets::Event<TestConfig>),
+    Nfts(nfts::Event<TestConfig>),
+    Staking(staking::Event<TestConfig>),
+    Democracy(democracy::Event<TestConfig>),
+    Collective(collective::Event<TestConfig>),
+    Treasury(treasury::Event<TestConfig>),
+    Identity(identity::Event<TestConfig>),
+}
+
+impl From<balances::Event<TestConfig>> for TestEvent {
+    fn from(event: balances::Event<TestConfig>) -> Self {
+        TestEvent::Balances(event)
+    }
+}
+
+impl From<balances::Event<TestConfig, Instance1>> for TestEvent {
+    fn from(event: balances::Event<TestConfig, Instance1>) -> Self {
+        TestEvent::BalancesInstance1(event)
+    }
+}
+
+impl From<multisig::Event<TestConfig>> for TestEvent {
+    fn from(event: multisig::Event<TestConfig>) -> Self {
+        TestEvent::Multisig(event)
+    }
+}
+
+/// The calls pallets dispatch in the tests.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TestCall {
+    Balances(balances::Call<TestConfig>),
+    Scheduler(scheduler::Call<TestConfig>),
+    /// A call which does nothing.
+    Remark,
+}
+
+impl Dispatchable for TestCall {
+    type Caller = types::AccountId;
+
+    fn dispatch(self, caller: Self::Caller) -> DispatchResult {
+        match self {
+            TestCall::Balances(call) => balances::Pallet::new().dispatch(caller, call),
+            TestCall::Scheduler(call) => scheduler::Pallet::new().dispatch(caller, call),
+            TestCall::Remark => Ok(()),
+        }
+    }
+}
+
+impl Encode for TestCall {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        match self {
+            TestCall::Balances(call) => {
+                dest.push(0);
+                call.encode_to(dest);
+            }
+            TestCall::Scheduler(call) => {
+                dest.push(1);
+                call.encode_to(dest);
+            }
+            TestCall::Remark => dest.push(2),
+        }
+    }
+}
+
+impl From<proxy::Event<TestConfig>> for TestEvent {
+    fn from(event: proxy::Event<TestConfig>) -> Self {
+        TestEvent::Proxy(event)
+    }
+}
+
+impl From<scheduler::Event<TestConfig>> for TestEvent {
+    fn from(event: scheduler::Event<TestConfig>) -> Self {
+        TestEvent::Scheduler(event)
+    }
+}
+
+impl From<timestamp::Event<TestConfig>> for TestEvent {
+    fn from(event: timestamp::Event<TestConfig>) -> Self {
+        TestEvent::Timestamp(event)
+    }
+}
+
+impl From<vesting::Event<TestConfig>> for TestEvent {
+    fn from(event: vesting::Event<TestConfig>) -> Self {
+        TestEvent::Vesting(event)
+    }
+}
+
+impl From<assets::Event<TestConfig>> for TestEvent {
+    fn from(event: assets::Event<TestConfig>) -> Self {
+        TestEvent::Assets(event)
+    }
+}
+
+impl From<nfts::Event<TestConfig>> for TestEvent {
+    fn from(event: nfts::Event<TestConfig>) -> Self {
+        TestEvent::Nfts(event)
+    }
+}
+
+impl From<staking::Event<TestConfig>> for TestEvent {
+    fn from(event: staking::Event<TestConfig>) -> Self {
+        TestEvent::Staking(event)
+    }
+}
+
+impl From<democracy::Event<TestConfig>> for TestEvent {
+    fn from(event: democracy::Event<TestConfig>) -> Self {
+        TestEvent::Democracy(event)
+    }
+}
+
+impl From<collective::Event<TestConfig>> for TestEvent {
+    fn from(event: collective::Event<TestConfig>) -> Self {
+        TestEvent::Collective(event)
+    }
+}
+
+impl From<treasury::Event<TestConfig>> for TestEvent {
+    fn from(event: treasury::Event<TestConfig>) -> Self {
+        TestEvent::Treasury(event)
+    }
+}
+
+impl From<identity::Event<TestConfig>> for TestEvent {
+    fn from(event: identity::Event<TestConfig>) -> Self {
+        TestEvent::Identity(event)
+    }
+}
+
+impl system::Config for TestConfig {
+    type AccountId = types::AccountId;
+    type BlockNumber = types::BlockNumber;
+    type Nonce = types::Nonce;
+    type RuntimeEvent = TestEvent;
+    type MaximumBlockWeight = ConstU64<1_000>;
+    type ExtrinsicBaseWeight = ConstU64<10>;
+    type RootAccount = types::RootAccount;
+}
+
+impl balances::Config for TestConfig {
+    type Balance = types::Balance;
+}
+
+/// A call transferring `amount` to `to`.
+pub fn transfer(to: &str, amount: u128) -> Box<TestCall> {
+    Box::new(TestCall::Balances(balances::Call::transfer {
+        to: to.to_string(),
+        amount,
+    }))
+}
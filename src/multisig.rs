@@ -0,0 +1,364 @@
+use std::marker::PhantomData;
+
+use crate::{
+    support::{
+        codec::Encode,
+        hashing::{hash_of, H256},
+        storage::{transactional, StorageMap},
+        traits::{Convert, Get, Hooks},
+        DispatchResult, Dispatchable,
+    },
+    system,
+};
+
+pub trait Config: system::Config<RuntimeEvent: From<Event<Self>>> {
+    /// The calls a multisig account dispatches, usually the `RuntimeCall` of the runtime.
+    type RuntimeCall: Dispatchable<Caller = Self::AccountId> + Encode;
+    /// Turns the hash identifying a set of signatories and a threshold into the account id of
+    /// their multisig account.
+    type AccountIdFromHash: Convert<H256, Self::AccountId>;
+    /// The largest number of signatories a multisig account can have, the caller included.
+    type MaxSignatories: Get<u32>;
+}
+
+/// An operation of a multisig account waiting for approvals.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Multisig<AccountId, BlockNumber> {
+    /// The block in which the first approval was given.
+    pub when: BlockNumber,
+    /// The signatory who started the operation. Only it can cancel the operation.
+    pub originator: AccountId,
+    /// The signatories who approved the operation, in order.
+    pub approvals: Vec<AccountId>,
+}
+
+pub type MultisigOf<T> =
+    Multisig<<T as system::Config>::AccountId, <T as system::Config>::BlockNumber>;
+
+/// Calls are referred to by their hash.
+#[allow(clippy::enum_variant_names)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event<T: Config> {
+    /// A signatory started a new operation.
+    NewMultisig {
+        approving: T::AccountId,
+        multisig: T::AccountId,
+        call_hash: H256,
+    },
+    /// A signatory approved an existing operation.
+    MultisigApproval {
+        approving: T::AccountId,
+        multisig: T::AccountId,
+        call_hash: H256,
+    },
+    /// The threshold was reached and the call was dispatched as the multisig account.
+    MultisigExecuted {
+        approving: T::AccountId,
+        multisig: T::AccountId,
+        call_hash: H256,
+        result: DispatchResult,
+    },
+    MultisigCancelled {
+        cancelling: T::AccountId,
+        multisig: T::AccountId,
+        call_hash: H256,
+    },
+}
+
+/// The pending operations, keyed by multisig account and call hash.
+pub struct Multisigs<T>(PhantomData<T>);
+impl<T: Config> StorageMap for Multisigs<T> {
+    type Key = (T::AccountId, H256);
+    type Value = MultisigOf<T>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pallet<T: Config>(PhantomData<T>);
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+    /// Approve the call with `call_hash` on behalf of the multisig account of the caller,
+    /// `other_signatories` and `threshold`, without dispatching it.
+    ///
+    /// The call is dispatched once a signatory submits it in full with `as_multi`.
+    pub fn approve_as_multi(
+        &mut self,
+        caller: T::AccountId,
+        threshold: u32,
+        other_signatories: Vec<T::AccountId>,
+        call_hash: H256,
+    ) -> DispatchResult {
+        let multisig = Self::checked_multi_account_id(&caller, threshold, other_signatories)?;
+        Self::approve(caller, multisig, call_hash)?;
+        Ok(())
+    }
+
+    /// Approve `call` like `approve_as_multi`, and dispatch it as the multisig account if the
+    /// threshold is reached with this approval.
+    ///
+    /// A caller who already approved the call can submit it again to dispatch it.
+    pub fn as_multi(
+        &mut self,
+        caller: T::AccountId,
+        threshold: u32,
+        other_signatories: Vec<T::AccountId>,
+        call: Box<T::RuntimeCall>,
+    ) -> DispatchResult {
+        let multisig = Self::checked_multi_account_id(&caller, threshold, other_signatories)?;
+        let call_hash = hash_of(&call);
+        let approvals = match Multisigs::<T>::get(&(multisig.clone(), call_hash)) {
+            Some(op) if op.approvals.contains(&caller) => op.approvals.len(),
+            _ => Self::approve(caller.clone(), multisig.clone(), call_hash)?,
+        };
+        if approvals < threshold as usize {
+            return Ok(());
+        }
+
+        Multisigs::<T>::remove(&(multisig.clone(), call_hash));
+        let result = transactional(|| call.dispatch(multisig.clone()));
+        system::Pallet::<T>::deposit_event(Event::MultisigExecuted {
+            approving: caller,
+            multisig,
+            call_hash,
+            result,
+        });
+        Ok(())
+    }
+
+    /// Cancel a pending operation. Only the signatory who started it can cancel it.
+    pub fn cancel_as_multi(
+        &mut self,
+        caller: T::AccountId,
+        threshold: u32,
+        other_signatories: Vec<T::AccountId>,
+        call_hash: H256,
+    ) -> DispatchResult {
+        let multisig = Self::checked_multi_account_id(&caller, threshold, other_signatories)?;
+        let key = (multisig.clone(), call_hash);
+        let op = Multisigs::<T>::get(&key).ok_or("Multisig operation not found")?;
+        if op.originator != caller {
+            return Err("Only the originator can cancel the operation");
+        }
+
+        Multisigs::<T>::remove(&key);
+        system::Pallet::<T>::deposit_event(Event::MultisigCancelled {
+            cancelling: caller,
+            multisig,
+            call_hash,
+        });
+        Ok(())
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn new() -> Self {
+        Pallet(PhantomData)
+    }
+
+    /// The account of the multisig with the given signatories and threshold.
+    ///
+    /// The order of the signatories does not matter.
+    pub fn multi_account_id(signatories: &[T::AccountId], threshold: u32) -> T::AccountId {
+        let mut signatories = signatories.to_vec();
+        signatories.sort();
+        T::AccountIdFromHash::convert(hash_of(&(b"multisig", signatories, threshold)))
+    }
+
+    pub fn multisig(multisig: &T::AccountId, call_hash: &H256) -> Option<MultisigOf<T>> {
+        Multisigs::<T>::get(&(multisig.clone(), *call_hash))
+    }
+
+    /// The multisig account of `caller` and `other_signatories`, after checking they form a
+    /// valid multisig with `threshold`.
+    fn checked_multi_account_id(
+        caller: &T::AccountId,
+        threshold: u32,
+        mut other_signatories: Vec<T::AccountId>,
+    ) -> Result<T::AccountId, &'static str> {
+        other_signatories.push(caller.clone());
+        let signatories = other_signatories;
+        if threshold == 0 {
+            return Err("Threshold must be at least one");
+        }
+        if threshold as usize > signatories.len() {
+            return Err("Threshold exceeds the number of signatories");
+        }
+        if signatories.len() > T::MaxSignatories::get() as usize {
+            return Err("Too many signatories");
+        }
+        let mut sorted = signatories.clone();
+        sorted.sort();
+        sorted.dedup();
+        if sorted.len() != signatories.len() {
+            return Err("Signatories must be distinct");
+        }
+        Ok(Self::multi_account_id(&signatories, threshold))
+    }
+
+    /// Record the approval of `approving`, starting the operation if needed.
+    ///
+    /// Returns the number of approvals of the operation.
+    fn approve(
+        approving: T::AccountId,
+        multisig: T::AccountId,
+        call_hash: H256,
+    ) -> Result<usize, &'static str> {
+        let key = (multisig.clone(), call_hash);
+        let event = match Multisigs::<T>::get(&key) {
+            Some(mut op) => {
+                if op.approvals.contains(&approving) {
+                    return Err("Already approved");
+                }
+                op.approvals.push(approving.clone());
+                Multisigs::<T>::insert(key, op);
+                Event::MultisigApproval {
+                    approving,
+                    multisig: multisig.clone(),
+                    call_hash,
+                }
+            }
+            None => {
+                Multisigs::<T>::insert(
+                    key,
+                    Multisig {
+                        when: system::Pallet::<T>::block_number(),
+                        originator: approving.clone(),
+                        approvals: vec![approving.clone()],
+                    },
+                );
+                Event::NewMultisig {
+                    approving,
+                    multisig: multisig.clone(),
+                    call_hash,
+                }
+            }
+        };
+        system::Pallet::<T>::deposit_event(event);
+        Ok(Self::multisig(&multisig, &call_hash).map_or(0, |op| op.approvals.len()))
+    }
+}
+
+impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        balances,
+        mock::{transfer, TestConfig, TestEvent},
+        support::traits::ConstU32,
+        types,
+    };
+
+    use super::*;
+
+    impl Config for TestConfig {
+        type RuntimeCall = crate::mock::TestCall;
+        type AccountIdFromHash = types::HexAccountId;
+        type MaxSignatories = ConstU32<3>;
+    }
+
+    type Balances = balances::Pallet<TestConfig>;
+    type Multisig = Pallet<TestConfig>;
+
+    fn accounts() -> (String, String, String) {
+        ("A".to_string(), "B".to_string(), "C".to_string())
+    }
+
+    #[test]
+    fn multi_account_id_is_deterministic() {
+        let (a, b, c) = accounts();
+        let id = Multisig::multi_account_id(&[a.clone(), b.clone(), c.clone()], 2);
+        assert_eq!(
+            Multisig::multi_account_id(&[c.clone(), a.clone(), b.clone()], 2),
+            id
+        );
+        assert_ne!(Multisig::multi_account_id(&[a, b, c], 3), id);
+    }
+
+    #[test]
+    fn call_is_dispatched_once_threshold_is_reached() {
+        let (a, b, c) = accounts();
+        let multisig = Multisig::multi_account_id(&[a.clone(), b.clone(), c.clone()], 2);
+        Balances::set_balance(&multisig, 100);
+        let mut pallet = Multisig::new();
+        let call_hash = hash_of(&transfer("D", 60));
+
+        pallet
+            .approve_as_multi(a.clone(), 2, vec![b.clone(), c.clone()], call_hash)
+            .unwrap();
+        assert_eq!(
+            pallet.approve_as_multi(a.clone(), 2, vec![b.clone(), c.clone()], call_hash),
+            Err("Already approved")
+        );
+        assert_eq!(Balances::balance(&"D".to_string()), 0);
+
+        pallet
+            .as_multi(c.clone(), 2, vec![a.clone(), b.clone()], transfer("D", 60))
+            .unwrap();
+        assert_eq!(Balances::balance(&"D".to_string()), 60);
+        assert_eq!(Balances::balance(&multisig), 40);
+        assert_eq!(Multisig::multisig(&multisig, &call_hash), None);
+        assert!(
+            system::Pallet::<TestConfig>::events().contains(&TestEvent::Multisig(
+                Event::MultisigExecuted {
+                    approving: c,
+                    multisig,
+                    call_hash,
+                    result: Ok(())
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn only_originator_can_cancel() {
+        let (a, b, c) = accounts();
+        let multisig = Multisig::multi_account_id(&[a.clone(), b.clone(), c.clone()], 3);
+        let mut pallet = Multisig::new();
+        let call_hash = hash_of(&transfer("D", 60));
+
+        pallet
+            .as_multi(a.clone(), 3, vec![b.clone(), c.clone()], transfer("D", 60))
+            .unwrap();
+        pallet
+            .approve_as_multi(b.clone(), 3, vec![a.clone(), c.clone()], call_hash)
+            .unwrap();
+        assert_eq!(
+            Multisig::multisig(&multisig, &call_hash).map(|op| op.approvals),
+            Some(vec![a.clone(), b.clone()])
+        );
+
+        assert_eq!(
+            pallet.cancel_as_multi(b.clone(), 3, vec![a.clone(), c.clone()], call_hash),
+            Err("Only the originator can cancel the operation")
+        );
+        pallet
+            .cancel_as_multi(a.clone(), 3, vec![b, c], call_hash)
+            .unwrap();
+        assert_eq!(Multisig::multisig(&multisig, &call_hash), None);
+    }
+
+    #[test]
+    fn invalid_signatories_are_rejected() {
+        let (a, b, c) = accounts();
+        let mut pallet = Multisig::new();
+        let call_hash = hash_of(&transfer("D", 60));
+
+        assert_eq!(
+            pallet.approve_as_multi(a.clone(), 0, vec![b.clone()], call_hash),
+            Err("Threshold must be at least one")
+        );
+        assert_eq!(
+            pallet.approve_as_multi(a.clone(), 3, vec![b.clone()], call_hash),
+            Err("Threshold exceeds the number of signatories")
+        );
+        assert_eq!(
+            pallet.approve_as_multi(a.clone(), 2, vec![b.clone(), b.clone()], call_hash),
+            Err("Signatories must be distinct")
+        );
+        assert_eq!(
+            pallet.approve_as_multi(a, 2, vec![b, c, "D".to_string()], call_hash),
+            Err("Too many signatories")
+        );
+    }
+}
@@ -0,0 +1,537 @@
+use std::{fmt::Debug, marker::PhantomData};
+
+use crate::{
+    support::{
+        codec::Encode,
+        storage::StorageMap,
+        traits::{Get, Hooks},
+        DispatchResult,
+    },
+    system,
+};
+
+pub trait Config: system::Config<RuntimeEvent: From<Event<Self>>> {
+    /// The identifier of a collection, chosen by its creator.
+    type CollectionId: Copy + Ord + Debug + Encode;
+    /// The identifier of an item within its collection, chosen by the collection owner.
+    type ItemId: Copy + Ord + Debug + Encode;
+    /// The longest attribute key, in bytes.
+    type KeyLimit: Get<u32>;
+    /// The longest attribute value, in bytes.
+    type ValueLimit: Get<u32>;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CollectionDetails<AccountId> {
+    /// The account that created the collection, which mints its items and sets their attributes.
+    pub owner: AccountId,
+    /// The number of items in the collection.
+    pub items: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ItemDetails<AccountId> {
+    pub owner: AccountId,
+    /// The account allowed to transfer the item on behalf of its owner.
+    pub approved: Option<AccountId>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event<T: Config> {
+    Created {
+        collection: T::CollectionId,
+        owner: T::AccountId,
+    },
+    Destroyed {
+        collection: T::CollectionId,
+    },
+    Issued {
+        collection: T::CollectionId,
+        item: T::ItemId,
+        owner: T::AccountId,
+    },
+    Burned {
+        collection: T::CollectionId,
+        item: T::ItemId,
+        owner: T::AccountId,
+    },
+    Transferred {
+        collection: T::CollectionId,
+        item: T::ItemId,
+        from: T::AccountId,
+        to: T::AccountId,
+    },
+    ApprovedTransfer {
+        collection: T::CollectionId,
+        item: T::ItemId,
+        owner: T::AccountId,
+        delegate: T::AccountId,
+    },
+    ApprovalCancelled {
+        collection: T::CollectionId,
+        item: T::ItemId,
+        owner: T::AccountId,
+    },
+    /// An attribute of the collection, or of one of its items if `maybe_item` is set, was set.
+    AttributeSet {
+        collection: T::CollectionId,
+        maybe_item: Option<T::ItemId>,
+        key: Vec<u8>,
+        value: Vec<u8>,
+    },
+    AttributeCleared {
+        collection: T::CollectionId,
+        maybe_item: Option<T::ItemId>,
+        key: Vec<u8>,
+    },
+}
+
+/// The details of each collection.
+pub struct Collection<T>(PhantomData<T>);
+impl<T: Config> StorageMap for Collection<T> {
+    type Key = T::CollectionId;
+    type Value = CollectionDetails<T::AccountId>;
+}
+
+/// The details of each item, keyed by its collection and its id within it.
+pub struct Item<T>(PhantomData<T>);
+impl<T: Config> StorageMap for Item<T> {
+    type Key = (T::CollectionId, T::ItemId);
+    type Value = ItemDetails<T::AccountId>;
+}
+
+/// The items owned by each account, in the order they were acquired.
+pub struct ItemsOf<T>(PhantomData<T>);
+impl<T: Config> StorageMap for ItemsOf<T> {
+    type Key = T::AccountId;
+    type Value = Vec<(T::CollectionId, T::ItemId)>;
+}
+
+/// The attributes of collections and items, keyed by the collection, the item if any, and the
+/// attribute key.
+pub struct Attribute<T>(PhantomData<T>);
+impl<T: Config> StorageMap for Attribute<T> {
+    type Key = (T::CollectionId, Option<T::ItemId>, Vec<u8>);
+    type Value = Vec<u8>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pallet<T: Config>(PhantomData<T>);
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+    /// Create the empty collection `collection`, owned by the caller.
+    pub fn create(&mut self, caller: T::AccountId, collection: T::CollectionId) -> DispatchResult {
+        if Collection::<T>::contains_key(&collection) {
+            return Err("Collection already exists");
+        }
+
+        Collection::<T>::insert(
+            collection,
+            CollectionDetails {
+                owner: caller.clone(),
+                items: 0,
+            },
+        );
+        system::Pallet::<T>::deposit_event(Event::Created {
+            collection,
+            owner: caller,
+        });
+        Ok(())
+    }
+
+    /// Remove a collection the caller owns, once all of its items were burned.
+    pub fn destroy(&mut self, caller: T::AccountId, collection: T::CollectionId) -> DispatchResult {
+        let details = Self::ensure_collection_owner(&caller, collection)?;
+        if details.items > 0 {
+            return Err("Collection is not empty");
+        }
+
+        Collection::<T>::remove(&collection);
+        Self::remove_attributes(|(c, _, _)| *c == collection);
+        system::Pallet::<T>::deposit_event(Event::Destroyed { collection });
+        Ok(())
+    }
+
+    /// Create the item `item` in a collection the caller owns, and give it to `owner`.
+    pub fn mint(
+        &mut self,
+        caller: T::AccountId,
+        collection: T::CollectionId,
+        item: T::ItemId,
+        owner: T::AccountId,
+    ) -> DispatchResult {
+        let mut details = Self::ensure_collection_owner(&caller, collection)?;
+        if Item::<T>::contains_key(&(collection, item)) {
+            return Err("Item already exists");
+        }
+        details.items = details.items.checked_add(1).ok_or("Collection is full")?;
+
+        Collection::<T>::insert(collection, details);
+        Item::<T>::insert(
+            (collection, item),
+            ItemDetails {
+                owner: owner.clone(),
+                approved: None,
+            },
+        );
+        ItemsOf::<T>::mutate(&owner, |items| {
+            items.get_or_insert_with(Vec::new).push((collection, item))
+        });
+        system::Pallet::<T>::deposit_event(Event::Issued {
+            collection,
+            item,
+            owner,
+        });
+        Ok(())
+    }
+
+    /// Destroy an item the caller owns, along with its attributes.
+    pub fn burn(
+        &mut self,
+        caller: T::AccountId,
+        collection: T::CollectionId,
+        item: T::ItemId,
+    ) -> DispatchResult {
+        Self::ensure_item_owner(&caller, collection, item)?;
+
+        Item::<T>::remove(&(collection, item));
+        Collection::<T>::mutate(&collection, |details| {
+            if let Some(details) = details {
+                details.items -= 1;
+            }
+        });
+        Self::remove_from_owner(&caller, collection, item);
+        Self::remove_attributes(|(c, i, _)| *c == collection && *i == Some(item));
+        system::Pallet::<T>::deposit_event(Event::Burned {
+            collection,
+            item,
+            owner: caller,
+        });
+        Ok(())
+    }
+
+    /// Move an item to `dest`. The caller must own the item or be approved to transfer it.
+    ///
+    /// Any approval is cleared, so the new owner starts without a delegate.
+    pub fn transfer(
+        &mut self,
+        caller: T::AccountId,
+        collection: T::CollectionId,
+        item: T::ItemId,
+        dest: T::AccountId,
+    ) -> DispatchResult {
+        let details = Self::item(collection, item)?;
+        if details.owner != caller && details.approved.as_ref() != Some(&caller) {
+            return Err("Caller is not the owner of the item nor approved to transfer it");
+        }
+
+        Item::<T>::insert(
+            (collection, item),
+            ItemDetails {
+                owner: dest.clone(),
+                approved: None,
+            },
+        );
+        Self::remove_from_owner(&details.owner, collection, item);
+        ItemsOf::<T>::mutate(&dest, |items| {
+            items.get_or_insert_with(Vec::new).push((collection, item))
+        });
+        system::Pallet::<T>::deposit_event(Event::Transferred {
+            collection,
+            item,
+            from: details.owner,
+            to: dest,
+        });
+        Ok(())
+    }
+
+    /// Allow `delegate` to transfer an item the caller owns, replacing any previous delegate.
+    pub fn approve_transfer(
+        &mut self,
+        caller: T::AccountId,
+        collection: T::CollectionId,
+        item: T::ItemId,
+        delegate: T::AccountId,
+    ) -> DispatchResult {
+        let mut details = Self::ensure_item_owner(&caller, collection, item)?;
+        details.approved = Some(delegate.clone());
+        Item::<T>::insert((collection, item), details);
+        system::Pallet::<T>::deposit_event(Event::ApprovedTransfer {
+            collection,
+            item,
+            owner: caller,
+            delegate,
+        });
+        Ok(())
+    }
+
+    pub fn cancel_approval(
+        &mut self,
+        caller: T::AccountId,
+        collection: T::CollectionId,
+        item: T::ItemId,
+    ) -> DispatchResult {
+        let mut details = Self::ensure_item_owner(&caller, collection, item)?;
+        details
+            .approved
+            .take()
+            .ok_or("Item has no approved delegate")?;
+        Item::<T>::insert((collection, item), details);
+        system::Pallet::<T>::deposit_event(Event::ApprovalCancelled {
+            collection,
+            item,
+            owner: caller,
+        });
+        Ok(())
+    }
+
+    /// Set an attribute of a collection the caller owns, or of one of its items if `maybe_item`
+    /// is set.
+    pub fn set_attribute(
+        &mut self,
+        caller: T::AccountId,
+        collection: T::CollectionId,
+        maybe_item: Option<T::ItemId>,
+        key: Vec<u8>,
+        value: Vec<u8>,
+    ) -> DispatchResult {
+        Self::ensure_collection_owner(&caller, collection)?;
+        if let Some(item) = maybe_item {
+            Self::item(collection, item)?;
+        }
+        if key.len() > T::KeyLimit::get() as usize {
+            return Err("Attribute key is too long");
+        }
+        if value.len() > T::ValueLimit::get() as usize {
+            return Err("Attribute value is too long");
+        }
+
+        Attribute::<T>::insert((collection, maybe_item, key.clone()), value.clone());
+        system::Pallet::<T>::deposit_event(Event::AttributeSet {
+            collection,
+            maybe_item,
+            key,
+            value,
+        });
+        Ok(())
+    }
+
+    pub fn clear_attribute(
+        &mut self,
+        caller: T::AccountId,
+        collection: T::CollectionId,
+        maybe_item: Option<T::ItemId>,
+        key: Vec<u8>,
+    ) -> DispatchResult {
+        Self::ensure_collection_owner(&caller, collection)?;
+        Attribute::<T>::remove(&(collection, maybe_item, key.clone()))
+            .ok_or("Attribute not found")?;
+        system::Pallet::<T>::deposit_event(Event::AttributeCleared {
+            collection,
+            maybe_item,
+            key,
+        });
+        Ok(())
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn new() -> Self {
+        Pallet(PhantomData)
+    }
+
+    pub fn collection(
+        collection: T::CollectionId,
+    ) -> Result<CollectionDetails<T::AccountId>, &'static str> {
+        Collection::<T>::get(&collection).ok_or("Collection not found")
+    }
+
+    pub fn item(
+        collection: T::CollectionId,
+        item: T::ItemId,
+    ) -> Result<ItemDetails<T::AccountId>, &'static str> {
+        Item::<T>::get(&(collection, item)).ok_or("Item not found")
+    }
+
+    /// The owner of an item, if it exists.
+    pub fn owner(collection: T::CollectionId, item: T::ItemId) -> Option<T::AccountId> {
+        Item::<T>::get(&(collection, item)).map(|details| details.owner)
+    }
+
+    /// All items `who` owns, as `(collection, item)` pairs.
+    pub fn items_of(who: &T::AccountId) -> Vec<(T::CollectionId, T::ItemId)> {
+        ItemsOf::<T>::get(who).unwrap_or_default()
+    }
+
+    pub fn attribute(
+        collection: T::CollectionId,
+        maybe_item: Option<T::ItemId>,
+        key: &[u8],
+    ) -> Option<Vec<u8>> {
+        Attribute::<T>::get(&(collection, maybe_item, key.to_vec()))
+    }
+
+    fn ensure_collection_owner(
+        who: &T::AccountId,
+        collection: T::CollectionId,
+    ) -> Result<CollectionDetails<T::AccountId>, &'static str> {
+        let details = Self::collection(collection)?;
+        if &details.owner != who {
+            return Err("Caller is not the owner of the collection");
+        }
+        Ok(details)
+    }
+
+    fn ensure_item_owner(
+        who: &T::AccountId,
+        collection: T::CollectionId,
+        item: T::ItemId,
+    ) -> Result<ItemDetails<T::AccountId>, &'static str> {
+        let details = Self::item(collection, item)?;
+        if &details.owner != who {
+            return Err("Caller is not the owner of the item");
+        }
+        Ok(details)
+    }
+
+    fn remove_from_owner(owner: &T::AccountId, collection: T::CollectionId, item: T::ItemId) {
+        ItemsOf::<T>::mutate(owner, |items| {
+            if let Some(list) = items {
+                list.retain(|i| *i != (collection, item));
+                if list.is_empty() {
+                    *items = None;
+                }
+            }
+        });
+    }
+
+    fn remove_attributes(filter: impl Fn(&(T::CollectionId, Option<T::ItemId>, Vec<u8>)) -> bool) {
+        for (key, _) in Attribute::<T>::iter() {
+            if filter(&key) {
+                Attribute::<T>::remove(&key);
+            }
+        }
+    }
+}
+
+impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {}
+
+#[cfg(test)]
+mod test {
+    use crate::{mock::TestConfig, support::traits::ConstU32, types};
+
+    use super::*;
+
+    impl Config for TestConfig {
+        type CollectionId = types::CollectionId;
+        type ItemId = types::ItemId;
+        type KeyLimit = ConstU32<8>;
+        type ValueLimit = ConstU32<32>;
+    }
+
+    type Nfts = Pallet<TestConfig>;
+
+    #[test]
+    fn collection_owner_mints_items() {
+        let (a, b) = ("A".to_string(), "B".to_string());
+        let mut nfts = Nfts::new();
+        nfts.create(a.clone(), 0).unwrap();
+        assert_eq!(nfts.create(b.clone(), 0), Err("Collection already exists"));
+
+        assert_eq!(
+            nfts.mint(b.clone(), 0, 1, b.clone()),
+            Err("Caller is not the owner of the collection")
+        );
+        nfts.mint(a.clone(), 0, 1, b.clone()).unwrap();
+        nfts.mint(a.clone(), 0, 2, b.clone()).unwrap();
+        assert_eq!(
+            nfts.mint(a.clone(), 0, 1, a.clone()),
+            Err("Item already exists")
+        );
+        assert_eq!(Nfts::owner(0, 1), Some(b.clone()));
+        assert_eq!(Nfts::items_of(&b), vec![(0, 1), (0, 2)]);
+
+        assert_eq!(nfts.destroy(a.clone(), 0), Err("Collection is not empty"));
+        assert_eq!(
+            nfts.burn(a.clone(), 0, 1),
+            Err("Caller is not the owner of the item")
+        );
+        nfts.burn(b.clone(), 0, 1).unwrap();
+        nfts.burn(b.clone(), 0, 2).unwrap();
+        assert!(Nfts::items_of(&b).is_empty());
+        nfts.destroy(a, 0).unwrap();
+        assert_eq!(Nfts::collection(0), Err("Collection not found"));
+    }
+
+    #[test]
+    fn approved_delegate_can_transfer_once() {
+        let (a, b, c) = ("A".to_string(), "B".to_string(), "C".to_string());
+        let mut nfts = Nfts::new();
+        nfts.create(a.clone(), 0).unwrap();
+        nfts.mint(a.clone(), 0, 1, a.clone()).unwrap();
+
+        assert_eq!(
+            nfts.transfer(b.clone(), 0, 1, b.clone()),
+            Err("Caller is not the owner of the item nor approved to transfer it")
+        );
+        nfts.approve_transfer(a.clone(), 0, 1, b.clone()).unwrap();
+        nfts.transfer(b.clone(), 0, 1, c.clone()).unwrap();
+        assert_eq!(Nfts::owner(0, 1), Some(c.clone()));
+        assert_eq!(Nfts::items_of(&c), vec![(0, 1)]);
+        assert!(Nfts::items_of(&a).is_empty());
+
+        // The approval does not survive the transfer.
+        assert_eq!(
+            nfts.transfer(b.clone(), 0, 1, b.clone()),
+            Err("Caller is not the owner of the item nor approved to transfer it")
+        );
+        nfts.approve_transfer(c.clone(), 0, 1, b.clone()).unwrap();
+        nfts.cancel_approval(c.clone(), 0, 1).unwrap();
+        assert_eq!(
+            nfts.cancel_approval(c, 0, 1),
+            Err("Item has no approved delegate")
+        );
+    }
+
+    #[test]
+    fn attributes_are_removed_with_their_item() {
+        let a = "A".to_string();
+        let mut nfts = Nfts::new();
+        nfts.create(a.clone(), 0).unwrap();
+        nfts.mint(a.clone(), 0, 1, a.clone()).unwrap();
+
+        assert_eq!(
+            nfts.set_attribute(
+                a.clone(),
+                0,
+                Some(1),
+                b"document-hash".to_vec(),
+                b"0xabcd".to_vec()
+            ),
+            Err("Attribute key is too long")
+        );
+        assert_eq!(
+            nfts.set_attribute(a.clone(), 0, Some(2), b"doc".to_vec(), b"0xabcd".to_vec()),
+            Err("Item not found")
+        );
+        nfts.set_attribute(a.clone(), 0, Some(1), b"doc".to_vec(), b"0xabcd".to_vec())
+            .unwrap();
+        nfts.set_attribute(a.clone(), 0, None, b"issuer".to_vec(), b"A".to_vec())
+            .unwrap();
+        assert_eq!(
+            Nfts::attribute(0, Some(1), b"doc"),
+            Some(b"0xabcd".to_vec())
+        );
+
+        nfts.burn(a.clone(), 0, 1).unwrap();
+        assert_eq!(Nfts::attribute(0, Some(1), b"doc"), None);
+        assert_eq!(Nfts::attribute(0, None, b"issuer"), Some(b"A".to_vec()));
+        nfts.clear_attribute(a.clone(), 0, None, b"issuer".to_vec())
+            .unwrap();
+        assert_eq!(
+            nfts.clear_attribute(a, 0, None, b"issuer".to_vec()),
+            Err("Attribute not found")
+        );
+    }
+}
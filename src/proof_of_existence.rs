@@ -1,80 +1,677 @@
-use std::{collections::BTreeMap, fmt::Debug};
+use std::{fmt::Debug, marker::PhantomData};
 
-use crate::{support::DispatchResult, system};
+use crate::{
+    support::{
+        hashing::{sha2_256, H256},
+        merkle::{self, Sibling},
+        storage::StorageMap,
+        traits::{Currency, Get, Hooks, NamedReservableCurrency, ReserveIdentifier},
+        DispatchResult,
+    },
+    system,
+};
 
-pub trait Config: system::Config {
-    type Content: Debug + Ord;
+/// The name under which claim deposits are reserved.
+pub const CLAIM_DEPOSIT_ID: ReserveIdentifier = *b"poeclaim";
+
+pub type BalanceOf<T> =
+    <<T as Config>::Currency as Currency<<T as system::Config>::AccountId>>::Balance;
+
+pub trait Config: system::Config<RuntimeEvent: From<Event<Self>>> {
+    /// The content claims are made on. Calls only carry its hash, given by `Pallet::hash_of`, so
+    /// the content itself never leaves the claimer.
+    type Content: AsRef<[u8]> + Debug + Clone;
+    /// The currency claims are paid with, usually provided by the balances pallet.
+    type Currency: NamedReservableCurrency<Self::AccountId, Balance: Clone>;
+    /// The amount reserved from the claimer for as long as a claim exists.
+    type ClaimDeposit: Get<BalanceOf<Self>>;
+}
+
+/// A claim on some content.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClaimInfo<AccountId, Balance, BlockNumber> {
+    pub owner: AccountId,
+    /// The amount reserved from the owner when the claim was created, released on revocation.
+    pub deposit: Balance,
+    /// The block in which the claim was created.
+    pub block_number: BlockNumber,
+    /// The last block in which the claim holds. It is pruned at the end of that block.
+    pub expires_at: Option<BlockNumber>,
+}
+
+pub type ClaimInfoOf<T> =
+    ClaimInfo<<T as system::Config>::AccountId, BalanceOf<T>, <T as system::Config>::BlockNumber>;
+
+/// Events refer to claims by the hash of their content.
+#[allow(clippy::enum_variant_names)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event<T: Config> {
+    ClaimCreated {
+        who: T::AccountId,
+        claim: H256,
+    },
+    ClaimRevoked {
+        who: T::AccountId,
+        claim: H256,
+    },
+    ClaimRenewed {
+        who: T::AccountId,
+        claim: H256,
+        expires_at: Option<T::BlockNumber>,
+    },
+    /// The claim lapsed and was pruned. Its deposit went back to the owner.
+    ClaimExpired {
+        who: T::AccountId,
+        claim: H256,
+    },
+    ClaimTransferOffered {
+        from: T::AccountId,
+        to: T::AccountId,
+        claim: H256,
+    },
+    ClaimTransferred {
+        from: T::AccountId,
+        to: T::AccountId,
+        claim: H256,
+    },
+}
+
+/// The claim on each claimed content, keyed by the hash of the content.
+pub struct Claims<T>(PhantomData<T>);
+impl<T: Config> StorageMap for Claims<T> {
+    type Key = H256;
+    type Value = ClaimInfoOf<T>;
 }
 
-#[derive(Debug)]
-pub struct Pallet<T: Config> {
-    claims: BTreeMap<T::Content, T::AccountId>,
+/// The claims held by each account, in the order they were acquired.
+pub struct ClaimsOf<T>(PhantomData<T>);
+impl<T: Config> StorageMap for ClaimsOf<T> {
+    type Key = T::AccountId;
+    type Value = Vec<H256>;
 }
 
+/// The account each claim was offered to, until it accepts the transfer.
+pub struct PendingTransfers<T>(PhantomData<T>);
+impl<T: Config> StorageMap for PendingTransfers<T> {
+    type Key = H256;
+    type Value = T::AccountId;
+}
+
+/// The claims that lapse at the end of each block.
+pub struct Expiries<T>(PhantomData<T>);
+impl<T: Config> StorageMap for Expiries<T> {
+    type Key = T::BlockNumber;
+    type Value = Vec<H256>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pallet<T: Config>(PhantomData<T>);
+
 #[macros::call]
 impl<T: Config> Pallet<T> {
-    pub fn create_claim(&mut self, caller: T::AccountId, claim: T::Content) -> DispatchResult {
-        match self.get_claim(&claim) {
-            Some(_) => Err("Claim already existed"),
-            None => {
-                self.claims.insert(claim, caller);
-                Ok(())
-            }
+    /// Claim the content whose hash is `claim`, as given by `hash_of`.
+    pub fn create_claim(
+        &mut self,
+        caller: T::AccountId,
+        claim: H256,
+        expires_at: Option<T::BlockNumber>,
+    ) -> DispatchResult {
+        Self::do_create_claim(caller, claim, expires_at)
+    }
+
+    pub fn revoke_claim(&mut self, caller: T::AccountId, claim: H256) -> DispatchResult {
+        Self::do_revoke_claim(caller, claim)
+    }
+
+    /// Claim every document of a batch at once, by claiming the root of the Merkle tree over
+    /// their hashes. See `support::merkle` to build the tree and the proofs of its documents.
+    pub fn create_batch_claim(
+        &mut self,
+        caller: T::AccountId,
+        root: H256,
+        expires_at: Option<T::BlockNumber>,
+    ) -> DispatchResult {
+        Self::do_create_claim(caller, root, expires_at)
+    }
+
+    pub fn revoke_batch_claim(&mut self, caller: T::AccountId, root: H256) -> DispatchResult {
+        Self::do_revoke_claim(caller, root)
+    }
+
+    /// Replace the expiry of a claim, or make it permanent with `None`.
+    pub fn renew_claim(
+        &mut self,
+        caller: T::AccountId,
+        claim: H256,
+        expires_at: Option<T::BlockNumber>,
+    ) -> DispatchResult {
+        let mut info = Claims::<T>::get(&claim).ok_or("Claim does not existed")?;
+        if info.owner != caller {
+            return Err("Caller is not the owner of claim");
         }
+        Self::ensure_future(expires_at)?;
+
+        Self::remove_expiry(claim, info.expires_at);
+        Self::add_expiry(claim, expires_at);
+        info.expires_at = expires_at;
+        Claims::<T>::insert(claim, info);
+        system::Pallet::<T>::deposit_event(Event::ClaimRenewed {
+            who: caller,
+            claim,
+            expires_at,
+        });
+        Ok(())
     }
 
-    pub fn revoke_claim(&mut self, caller: T::AccountId, claim: T::Content) -> DispatchResult {
-        let owner = self.get_claim(&claim).ok_or("Claim does not existed")?;
-        if owner != &caller {
+    /// Offer a claim to `new_owner`, who takes it over with `accept_claim`.
+    ///
+    /// A new offer replaces the previous one, and the owner keeps the claim until it is accepted.
+    pub fn transfer_claim(
+        &mut self,
+        caller: T::AccountId,
+        claim: H256,
+        new_owner: T::AccountId,
+    ) -> DispatchResult {
+        let info = Claims::<T>::get(&claim).ok_or("Claim does not existed")?;
+        if info.owner != caller {
             return Err("Caller is not the owner of claim");
         }
+        if new_owner == caller {
+            return Err("Cannot transfer a claim to its owner");
+        }
+
+        PendingTransfers::<T>::insert(claim, new_owner.clone());
+        system::Pallet::<T>::deposit_event(Event::ClaimTransferOffered {
+            from: caller,
+            to: new_owner,
+            claim,
+        });
+        Ok(())
+    }
+
+    /// Take over a claim offered to the caller. The caller reserves the deposit of the claim, and
+    /// the deposit of the previous owner is released.
+    pub fn accept_claim(&mut self, caller: T::AccountId, claim: H256) -> DispatchResult {
+        if PendingTransfers::<T>::get(&claim).as_ref() != Some(&caller) {
+            return Err("Claim was not offered to caller");
+        }
+        let mut info = Claims::<T>::get(&claim).ok_or("Claim does not existed")?;
 
-        self.claims.remove(&claim);
+        T::Currency::reserve_named(&CLAIM_DEPOSIT_ID, &caller, info.deposit.clone())?;
+        T::Currency::unreserve_named(&CLAIM_DEPOSIT_ID, &info.owner, info.deposit.clone());
+        PendingTransfers::<T>::remove(&claim);
+        Self::remove_from_owner(claim, &info.owner);
+        ClaimsOf::<T>::mutate(&caller, |claims| {
+            claims.get_or_insert_with(Vec::new).push(claim)
+        });
+        let from = std::mem::replace(&mut info.owner, caller.clone());
+        Claims::<T>::insert(claim, info);
+        system::Pallet::<T>::deposit_event(Event::ClaimTransferred {
+            from,
+            to: caller,
+            claim,
+        });
         Ok(())
     }
 }
 
 impl<T: Config> Pallet<T> {
     pub fn new() -> Self {
-        Self {
-            claims: BTreeMap::new(),
+        Pallet(PhantomData)
+    }
+
+    /// The hash claims on `content` are keyed by.
+    pub fn hash_of(content: &T::Content) -> H256 {
+        sha2_256(content.as_ref())
+    }
+
+    /// The owner of `claim`, if it was claimed.
+    pub fn get_claim(claim: &T::Content) -> Option<T::AccountId> {
+        Self::claim_info(&Self::hash_of(claim)).map(|info| info.owner)
+    }
+
+    pub fn claim_info(hash: &H256) -> Option<ClaimInfoOf<T>> {
+        Claims::<T>::get(hash)
+    }
+
+    /// The hashes of all claims `who` holds.
+    pub fn claims_of(who: &T::AccountId) -> Vec<H256> {
+        ClaimsOf::<T>::get(who).unwrap_or_default()
+    }
+
+    /// The account a claim was offered to, if its transfer is pending.
+    pub fn pending_transfer(hash: &H256) -> Option<T::AccountId> {
+        PendingTransfers::<T>::get(hash)
+    }
+
+    /// The claim on the batch with the given `root`, if `proof` shows that `document` is part of
+    /// it.
+    pub fn verify_batch_document(
+        root: &H256,
+        document: &T::Content,
+        proof: &[Sibling],
+    ) -> Option<ClaimInfoOf<T>> {
+        if !merkle::verify_proof(root, &Self::hash_of(document), proof) {
+            return None;
         }
+        Self::claim_info(root)
     }
 
-    pub fn get_claim(&self, claim: &T::Content) -> Option<&T::AccountId> {
-        self.claims.get(claim)
+    fn do_create_claim(
+        caller: T::AccountId,
+        claim: H256,
+        expires_at: Option<T::BlockNumber>,
+    ) -> DispatchResult {
+        if Claims::<T>::contains_key(&claim) {
+            return Err("Claim already existed");
+        }
+        Self::ensure_future(expires_at)?;
+
+        let deposit = T::ClaimDeposit::get();
+        T::Currency::reserve_named(&CLAIM_DEPOSIT_ID, &caller, deposit.clone())?;
+        Claims::<T>::insert(
+            claim,
+            ClaimInfo {
+                owner: caller.clone(),
+                deposit,
+                block_number: system::Pallet::<T>::block_number(),
+                expires_at,
+            },
+        );
+        Self::add_expiry(claim, expires_at);
+        ClaimsOf::<T>::mutate(&caller, |claims| {
+            claims.get_or_insert_with(Vec::new).push(claim)
+        });
+        system::Pallet::<T>::deposit_event(Event::ClaimCreated { who: caller, claim });
+        Ok(())
+    }
+
+    fn do_revoke_claim(caller: T::AccountId, claim: H256) -> DispatchResult {
+        let info = Claims::<T>::get(&claim).ok_or("Claim does not existed")?;
+        if info.owner != caller {
+            return Err("Caller is not the owner of claim");
+        }
+
+        Self::remove_claim(claim, info);
+        system::Pallet::<T>::deposit_event(Event::ClaimRevoked { who: caller, claim });
+        Ok(())
+    }
+
+    /// Remove a claim with everything that refers to it, and release its deposit.
+    fn remove_claim(claim: H256, info: ClaimInfoOf<T>) {
+        Claims::<T>::remove(&claim);
+        PendingTransfers::<T>::remove(&claim);
+        Self::remove_expiry(claim, info.expires_at);
+        Self::remove_from_owner(claim, &info.owner);
+        // The deposit is held under its own name, so nothing else can have taken it.
+        T::Currency::unreserve_named(&CLAIM_DEPOSIT_ID, &info.owner, info.deposit);
+    }
+
+    fn remove_from_owner(claim: H256, owner: &T::AccountId) {
+        ClaimsOf::<T>::mutate(owner, |claims| {
+            if let Some(list) = claims {
+                list.retain(|c| *c != claim);
+                if list.is_empty() {
+                    *claims = None;
+                }
+            }
+        });
+    }
+
+    fn ensure_future(expires_at: Option<T::BlockNumber>) -> DispatchResult {
+        match expires_at {
+            Some(n) if n <= system::Pallet::<T>::block_number() => {
+                Err("Claim expiry must be a future block")
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn add_expiry(claim: H256, expires_at: Option<T::BlockNumber>) {
+        if let Some(n) = expires_at {
+            Expiries::<T>::mutate(&n, |claims| claims.get_or_insert_with(Vec::new).push(claim));
+        }
+    }
+
+    fn remove_expiry(claim: H256, expires_at: Option<T::BlockNumber>) {
+        if let Some(n) = expires_at {
+            Expiries::<T>::mutate(&n, |claims| {
+                if let Some(list) = claims {
+                    list.retain(|c| *c != claim);
+                    if list.is_empty() {
+                        *claims = None;
+                    }
+                }
+            });
+        }
+    }
+}
+
+impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
+    /// Prune the claims that lapse in block `n` and release their deposits.
+    fn on_finalize(n: T::BlockNumber) {
+        for claim in Expiries::<T>::remove(&n).unwrap_or_default() {
+            if let Some(info) = Claims::<T>::get(&claim) {
+                let who = info.owner.clone();
+                Self::remove_claim(claim, info);
+                system::Pallet::<T>::deposit_event(Event::ClaimExpired { who, claim });
+            }
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::types;
+    use crate::{
+        balances,
+        support::traits::{ConstU128, ConstU64, ReservableCurrency},
+        types,
+    };
 
     use super::*;
 
+    #[derive(Clone, Debug, PartialEq, Eq)]
     struct TestConfig;
 
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    enum TestEvent {
+        Balances(balances::Event<TestConfig>),
+        ProofOfExistence(Event<TestConfig>),
+    }
+
+    impl From<balances::Event<TestConfig>> for TestEvent {
+        fn from(event: balances::Event<TestConfig>) -> Self {
+            TestEvent::Balances(event)
+        }
+    }
+
+    impl From<Event<TestConfig>> for TestEvent {
+        fn from(event: Event<TestConfig>) -> Self {
+            TestEvent::ProofOfExistence(event)
+        }
+    }
+
     impl system::Config for TestConfig {
         type AccountId = types::AccountId;
         type BlockNumber = types::BlockNumber;
         type Nonce = types::Nonce;
+        type RuntimeEvent = TestEvent;
+        type MaximumBlockWeight = ConstU64<1_000>;
+        type ExtrinsicBaseWeight = ConstU64<10>;
+        type RootAccount = types::RootAccount;
+    }
+
+    impl balances::Config for TestConfig {
+        type Balance = types::Balance;
     }
 
     impl Config for TestConfig {
         type Content = &'static str;
+        type Currency = balances::Pallet<TestConfig>;
+        type ClaimDeposit = ConstU128<10>;
+    }
+
+    type Balances = balances::Pallet<TestConfig>;
+
+    fn hash(content: &'static str) -> H256 {
+        Pallet::<TestConfig>::hash_of(&content)
     }
 
     #[test]
     fn basic_proof_of_existence() {
+        Balances::set_balance(&"A".to_string(), 100);
+        Balances::set_balance(&"B".to_string(), 100);
         let mut poe: Pallet<TestConfig> = Pallet::new();
-        assert_eq!(poe.get_claim(&"A"), None);
-        poe.create_claim("A".to_string(), "transactionA").unwrap();
-        assert_eq!(poe.get_claim(&"transactionA"), Some(&"A".to_string()));
+        assert_eq!(Pallet::<TestConfig>::get_claim(&"A"), None);
+        poe.create_claim("A".to_string(), hash("transactionA"), None)
+            .unwrap();
+        assert_eq!(
+            Pallet::<TestConfig>::get_claim(&"transactionA"),
+            Some("A".to_string())
+        );
 
-        let res = poe.create_claim("B".to_string(), "transactionA");
+        let res = poe.create_claim("B".to_string(), hash("transactionA"), None);
         assert_eq!(res, Err("Claim already existed"));
 
-        poe.revoke_claim("A".to_string(), "transactionA").unwrap();
-        assert_eq!(poe.get_claim(&"transactionA"), None);
+        poe.revoke_claim("A".to_string(), hash("transactionA"))
+            .unwrap();
+        assert_eq!(Pallet::<TestConfig>::get_claim(&"transactionA"), None);
+        let claim = sha2_256(b"transactionA");
+        let events = system::Pallet::<TestConfig>::events();
+        assert!(
+            events.contains(&TestEvent::ProofOfExistence(Event::ClaimCreated {
+                who: "A".to_string(),
+                claim
+            }))
+        );
+        assert!(
+            events.contains(&TestEvent::ProofOfExistence(Event::ClaimRevoked {
+                who: "A".to_string(),
+                claim
+            }))
+        );
+    }
+
+    #[test]
+    fn claim_deposit_is_reserved_and_released() {
+        let a = "A".to_string();
+        Balances::set_balance(&a, 100);
+        let mut poe: Pallet<TestConfig> = Pallet::new();
+
+        poe.create_claim(a.clone(), hash("transactionA"), None)
+            .unwrap();
+        assert_eq!(Balances::free_balance(&a), 90);
+        assert_eq!(Balances::reserved_balance(&a), 10);
+        assert_eq!(
+            Pallet::<TestConfig>::claim_info(&sha2_256(b"transactionA")),
+            Some(ClaimInfo {
+                owner: a.clone(),
+                deposit: 10,
+                block_number: 0,
+                expires_at: None,
+            })
+        );
+
+        poe.revoke_claim(a.clone(), hash("transactionA")).unwrap();
+        assert_eq!(Balances::free_balance(&a), 100);
+        assert_eq!(Balances::reserved_balance(&a), 0);
+    }
+
+    #[test]
+    fn claim_with_insufficient_balance() {
+        let a = "A".to_string();
+        Balances::set_balance(&a, 5);
+        let mut poe: Pallet<TestConfig> = Pallet::new();
+
+        let res = poe.create_claim(a.clone(), hash("transactionA"), None);
+        assert_eq!(res, Err("Insufficient fund"));
+        assert_eq!(Pallet::<TestConfig>::get_claim(&"transactionA"), None);
+        assert_eq!(Balances::free_balance(&a), 5);
+    }
+
+    #[test]
+    fn owner_balance_changes_between_claim_and_revocation() {
+        let a = "A".to_string();
+        let b = "B".to_string();
+        Balances::set_balance(&a, 100);
+        let mut poe: Pallet<TestConfig> = Pallet::new();
+        let mut balances: Balances = balances::Pallet::new();
+        poe.create_claim(a.clone(), hash("transactionA"), None)
+            .unwrap();
+
+        // The owner spends all of its free balance. The deposit cannot be spent with it.
+        assert_eq!(
+            balances.transfer(a.clone(), b.clone(), 91),
+            Err("Insufficient fund")
+        );
+        balances.transfer(a.clone(), b.clone(), 90).unwrap();
+        // Unrelated reserves of the owner do not affect the deposit.
+        Balances::set_balance(&a, 30);
+        Balances::reserve(&a, 30).unwrap();
+        assert_eq!(Balances::unreserve(&a, 40), 10);
+
+        poe.revoke_claim(a.clone(), hash("transactionA")).unwrap();
+        assert_eq!(Balances::free_balance(&a), 40);
+        assert_eq!(Balances::reserved_balance(&a), 0);
+        assert_eq!(Balances::free_balance(&b), 90);
+    }
+
+    #[test]
+    fn claims_expire_at_block_end() {
+        let a = "A".to_string();
+        Balances::set_balance(&a, 100);
+        let mut poe: Pallet<TestConfig> = Pallet::new();
+        system::Pallet::<TestConfig>::inc_block_number();
+
+        assert_eq!(
+            poe.create_claim(a.clone(), hash("transactionA"), Some(1)),
+            Err("Claim expiry must be a future block")
+        );
+        poe.create_claim(a.clone(), hash("transactionA"), Some(2))
+            .unwrap();
+        poe.create_claim(a.clone(), hash("transactionB"), Some(2))
+            .unwrap();
+        assert_eq!(Balances::reserved_balance(&a), 20);
+
+        // Renewing moves the claim out of block 2.
+        poe.renew_claim(a.clone(), hash("transactionB"), Some(3))
+            .unwrap();
+        assert_eq!(
+            Pallet::<TestConfig>::claim_info(&sha2_256(b"transactionB"))
+                .unwrap()
+                .expires_at,
+            Some(3)
+        );
+
+        Pallet::<TestConfig>::on_finalize(2);
+        assert_eq!(Pallet::<TestConfig>::get_claim(&"transactionA"), None);
+        assert_eq!(
+            Pallet::<TestConfig>::get_claim(&"transactionB"),
+            Some(a.clone())
+        );
+        assert_eq!(Balances::reserved_balance(&a), 10);
+        assert!(
+            system::Pallet::<TestConfig>::events().contains(&TestEvent::ProofOfExistence(
+                Event::ClaimExpired {
+                    who: a.clone(),
+                    claim: sha2_256(b"transactionA"),
+                }
+            ))
+        );
+
+        // A permanent claim is never pruned.
+        poe.renew_claim(a.clone(), hash("transactionB"), None)
+            .unwrap();
+        Pallet::<TestConfig>::on_finalize(3);
+        assert_eq!(Pallet::<TestConfig>::get_claim(&"transactionB"), Some(a));
+    }
+
+    #[test]
+    fn claims_are_transferred_on_acceptance() {
+        let a = "A".to_string();
+        let b = "B".to_string();
+        Balances::set_balance(&a, 100);
+        Balances::set_balance(&b, 100);
+        let mut poe: Pallet<TestConfig> = Pallet::new();
+        let claim = sha2_256(b"transactionA");
+        poe.create_claim(a.clone(), hash("transactionA"), None)
+            .unwrap();
+        poe.create_claim(a.clone(), hash("transactionB"), None)
+            .unwrap();
+
+        assert_eq!(
+            poe.transfer_claim(b.clone(), hash("transactionA"), b.clone()),
+            Err("Caller is not the owner of claim")
+        );
+        assert_eq!(
+            poe.accept_claim(b.clone(), hash("transactionA")),
+            Err("Claim was not offered to caller")
+        );
+        poe.transfer_claim(a.clone(), hash("transactionA"), b.clone())
+            .unwrap();
+        assert_eq!(
+            Pallet::<TestConfig>::pending_transfer(&claim),
+            Some(b.clone())
+        );
+        // The owner keeps the claim until the offer is accepted.
+        assert_eq!(
+            Pallet::<TestConfig>::get_claim(&"transactionA"),
+            Some(a.clone())
+        );
+
+        poe.accept_claim(b.clone(), hash("transactionA")).unwrap();
+        assert_eq!(
+            Pallet::<TestConfig>::get_claim(&"transactionA"),
+            Some(b.clone())
+        );
+        assert_eq!(Pallet::<TestConfig>::pending_transfer(&claim), None);
+        assert_eq!(
+            Pallet::<TestConfig>::claims_of(&a),
+            vec![sha2_256(b"transactionB")]
+        );
+        assert_eq!(Pallet::<TestConfig>::claims_of(&b), vec![claim]);
+        assert_eq!(Balances::reserved_balance(&a), 10);
+        assert_eq!(Balances::reserved_balance(&b), 10);
+
+        poe.revoke_claim(b.clone(), hash("transactionA")).unwrap();
+        assert!(Pallet::<TestConfig>::claims_of(&b).is_empty());
+        assert_eq!(Balances::reserved_balance(&b), 0);
+    }
+
+    #[test]
+    fn revoking_a_claim_cancels_its_transfer() {
+        let a = "A".to_string();
+        let b = "B".to_string();
+        Balances::set_balance(&a, 100);
+        Balances::set_balance(&b, 100);
+        let mut poe: Pallet<TestConfig> = Pallet::new();
+        poe.create_claim(a.clone(), hash("transactionA"), None)
+            .unwrap();
+        poe.transfer_claim(a.clone(), hash("transactionA"), b.clone())
+            .unwrap();
+        poe.revoke_claim(a.clone(), hash("transactionA")).unwrap();
+
+        // Claiming the content again does not revive the old offer.
+        poe.create_claim(a.clone(), hash("transactionA"), None)
+            .unwrap();
+        assert_eq!(
+            poe.accept_claim(b, hash("transactionA")),
+            Err("Claim was not offered to caller")
+        );
+    }
+
+    #[test]
+    fn batch_claims_prove_each_document() {
+        let a = "A".to_string();
+        Balances::set_balance(&a, 100);
+        let mut poe: Pallet<TestConfig> = Pallet::new();
+        let documents = ["doc0", "doc1", "doc2"];
+        let leaves: Vec<H256> = documents
+            .iter()
+            .map(Pallet::<TestConfig>::hash_of)
+            .collect();
+        let root = merkle::merkle_root(&leaves).unwrap();
+
+        poe.create_batch_claim(a.clone(), root, None).unwrap();
+        // The whole batch costs a single deposit.
+        assert_eq!(Balances::reserved_balance(&a), 10);
+
+        for (index, document) in documents.iter().enumerate() {
+            let proof = merkle::merkle_proof(&leaves, index).unwrap();
+            let info = Pallet::<TestConfig>::verify_batch_document(&root, document, &proof);
+            assert_eq!(info.map(|info| info.owner), Some(a.clone()));
+        }
+        let proof = merkle::merkle_proof(&leaves, 0).unwrap();
+        assert_eq!(
+            Pallet::<TestConfig>::verify_batch_document(&root, &"doc3", &proof),
+            None
+        );
+
+        poe.revoke_batch_claim(a.clone(), root).unwrap();
+        assert_eq!(
+            Pallet::<TestConfig>::verify_batch_document(&root, &"doc0", &proof),
+            None
+        );
+        assert_eq!(Balances::reserved_balance(&a), 0);
     }
 }
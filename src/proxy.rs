@@ -0,0 +1,552 @@
+use std::{fmt::Debug, marker::PhantomData};
+
+use num::traits::{CheckedAdd, Zero};
+
+use crate::{
+    support::{
+        codec::Encode,
+        hashing::{hash_of, H256},
+        storage::{transactional, StorageMap},
+        traits::{Get, Hooks, InstanceFilter},
+        DispatchResult, Dispatchable,
+    },
+    system,
+};
+
+pub trait Config: system::Config<RuntimeEvent: From<Event<Self>>> {
+    /// The calls a proxy dispatches, usually the `RuntimeCall` of the runtime.
+    type RuntimeCall: Dispatchable<Caller = Self::AccountId> + Encode;
+    /// The kinds of proxy, each allowing a subset of the calls.
+    type ProxyType: InstanceFilter<Self::RuntimeCall> + Clone + Debug + Eq + Encode;
+    /// The largest number of proxies an account can have.
+    type MaxProxies: Get<u32>;
+    /// The largest number of announcements a proxy can have pending.
+    type MaxPending: Get<u32>;
+}
+
+/// A delegate allowed to dispatch some calls on behalf of an account.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProxyDefinition<AccountId, ProxyType, BlockNumber> {
+    pub delegate: AccountId,
+    pub proxy_type: ProxyType,
+    /// The number of blocks a call must be announced for before the delegate can dispatch it.
+    /// With a zero delay, calls are dispatched right away.
+    pub delay: BlockNumber,
+}
+
+pub type ProxyDefinitionOf<T> = ProxyDefinition<
+    <T as system::Config>::AccountId,
+    <T as Config>::ProxyType,
+    <T as system::Config>::BlockNumber,
+>;
+
+/// A call a delegate announced it will dispatch on behalf of `real`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Announcement<AccountId, BlockNumber> {
+    pub real: AccountId,
+    pub call_hash: H256,
+    /// The block in which the call was announced.
+    pub height: BlockNumber,
+}
+
+pub type AnnouncementOf<T> =
+    Announcement<<T as system::Config>::AccountId, <T as system::Config>::BlockNumber>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event<T: Config> {
+    ProxyAdded {
+        delegator: T::AccountId,
+        delegate: T::AccountId,
+        proxy_type: T::ProxyType,
+        delay: T::BlockNumber,
+    },
+    ProxyRemoved {
+        delegator: T::AccountId,
+        delegate: T::AccountId,
+        proxy_type: T::ProxyType,
+        delay: T::BlockNumber,
+    },
+    Announced {
+        real: T::AccountId,
+        proxy: T::AccountId,
+        call_hash: H256,
+    },
+    /// A call was dispatched on behalf of an account, with the given result.
+    ProxyExecuted { result: DispatchResult },
+}
+
+/// The proxies of each account.
+pub struct Proxies<T>(PhantomData<T>);
+impl<T: Config> StorageMap for Proxies<T> {
+    type Key = T::AccountId;
+    type Value = Vec<ProxyDefinitionOf<T>>;
+}
+
+/// The pending announcements of each delegate.
+pub struct Announcements<T>(PhantomData<T>);
+impl<T: Config> StorageMap for Announcements<T> {
+    type Key = T::AccountId;
+    type Value = Vec<AnnouncementOf<T>>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pallet<T: Config>(PhantomData<T>);
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+    /// Allow `delegate` to dispatch the calls `proxy_type` allows on behalf of the caller.
+    pub fn add_proxy(
+        &mut self,
+        caller: T::AccountId,
+        delegate: T::AccountId,
+        proxy_type: T::ProxyType,
+        delay: T::BlockNumber,
+    ) -> DispatchResult {
+        if delegate == caller {
+            return Err("Cannot add self as proxy");
+        }
+        let definition = ProxyDefinition {
+            delegate: delegate.clone(),
+            proxy_type: proxy_type.clone(),
+            delay,
+        };
+        let mut proxies = Self::proxies(&caller);
+        if proxies.contains(&definition) {
+            return Err("Proxy already exists");
+        }
+        if proxies.len() >= T::MaxProxies::get() as usize {
+            return Err("Too many proxies");
+        }
+
+        proxies.push(definition);
+        Proxies::<T>::insert(caller.clone(), proxies);
+        system::Pallet::<T>::deposit_event(Event::ProxyAdded {
+            delegator: caller,
+            delegate,
+            proxy_type,
+            delay,
+        });
+        Ok(())
+    }
+
+    pub fn remove_proxy(
+        &mut self,
+        caller: T::AccountId,
+        delegate: T::AccountId,
+        proxy_type: T::ProxyType,
+        delay: T::BlockNumber,
+    ) -> DispatchResult {
+        let definition = ProxyDefinition {
+            delegate: delegate.clone(),
+            proxy_type: proxy_type.clone(),
+            delay,
+        };
+        let mut proxies = Self::proxies(&caller);
+        let index = proxies
+            .iter()
+            .position(|proxy| *proxy == definition)
+            .ok_or("Proxy not found")?;
+
+        proxies.remove(index);
+        if !proxies.iter().any(|proxy| proxy.delegate == delegate) {
+            Self::remove_announcements(&delegate, &caller);
+        }
+        if proxies.is_empty() {
+            Proxies::<T>::remove(&caller);
+        } else {
+            Proxies::<T>::insert(caller.clone(), proxies);
+        }
+        system::Pallet::<T>::deposit_event(Event::ProxyRemoved {
+            delegator: caller,
+            delegate,
+            proxy_type,
+            delay,
+        });
+        Ok(())
+    }
+
+    /// Remove every proxy of the caller, with the announcements they made on its behalf.
+    pub fn remove_proxies(&mut self, caller: T::AccountId) -> DispatchResult {
+        for proxy in Proxies::<T>::remove(&caller).unwrap_or_default() {
+            Self::remove_announcements(&proxy.delegate, &caller);
+            system::Pallet::<T>::deposit_event(Event::ProxyRemoved {
+                delegator: caller.clone(),
+                delegate: proxy.delegate,
+                proxy_type: proxy.proxy_type,
+                delay: proxy.delay,
+            });
+        }
+        Ok(())
+    }
+
+    /// Dispatch `call` on behalf of `real`, which must have the caller as a proxy without delay
+    /// whose type allows the call.
+    pub fn proxy(
+        &mut self,
+        caller: T::AccountId,
+        real: T::AccountId,
+        call: Box<T::RuntimeCall>,
+    ) -> DispatchResult {
+        let proxies = Self::find_proxies(&real, &caller, &call)?;
+        if !proxies.iter().any(|proxy| proxy.delay.is_zero()) {
+            return Err("Call must be announced first");
+        }
+
+        Self::do_proxy(real, *call);
+        Ok(())
+    }
+
+    /// Announce that the caller will dispatch the call with `call_hash` on behalf of `real`, once
+    /// the delay of its proxy has passed.
+    pub fn announce(
+        &mut self,
+        caller: T::AccountId,
+        real: T::AccountId,
+        call_hash: H256,
+    ) -> DispatchResult {
+        if !Self::proxies(&real)
+            .iter()
+            .any(|proxy| proxy.delegate == caller)
+        {
+            return Err("Not a proxy");
+        }
+        let mut announcements = Self::announcements(&caller);
+        if announcements.len() >= T::MaxPending::get() as usize {
+            return Err("Too many pending announcements");
+        }
+
+        announcements.push(Announcement {
+            real: real.clone(),
+            call_hash,
+            height: system::Pallet::<T>::block_number(),
+        });
+        Announcements::<T>::insert(caller.clone(), announcements);
+        system::Pallet::<T>::deposit_event(Event::Announced {
+            real,
+            proxy: caller,
+            call_hash,
+        });
+        Ok(())
+    }
+
+    /// Withdraw an announcement of the caller.
+    pub fn remove_announcement(
+        &mut self,
+        caller: T::AccountId,
+        real: T::AccountId,
+        call_hash: H256,
+    ) -> DispatchResult {
+        Self::take_announcement(&caller, &real, call_hash, |_| true).map(|_| ())
+    }
+
+    /// Reject an announcement a proxy of the caller made on its behalf.
+    pub fn reject_announcement(
+        &mut self,
+        caller: T::AccountId,
+        delegate: T::AccountId,
+        call_hash: H256,
+    ) -> DispatchResult {
+        Self::take_announcement(&delegate, &caller, call_hash, |_| true).map(|_| ())
+    }
+
+    /// Dispatch a call `delegate` announced on behalf of `real`, once the delay of the proxy has
+    /// passed. Anyone can submit it.
+    pub fn proxy_announced(
+        &mut self,
+        _caller: T::AccountId,
+        delegate: T::AccountId,
+        real: T::AccountId,
+        call: Box<T::RuntimeCall>,
+    ) -> DispatchResult {
+        let proxies = Self::find_proxies(&real, &delegate, &call)?;
+        let now = system::Pallet::<T>::block_number();
+        Self::take_announcement(&delegate, &real, hash_of(&call), |announcement| {
+            proxies.iter().any(|proxy| {
+                announcement
+                    .height
+                    .checked_add(&proxy.delay)
+                    .is_some_and(|due| due <= now)
+            })
+        })?;
+
+        Self::do_proxy(real, *call);
+        Ok(())
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn new() -> Self {
+        Pallet(PhantomData)
+    }
+
+    pub fn proxies(real: &T::AccountId) -> Vec<ProxyDefinitionOf<T>> {
+        Proxies::<T>::get(real).unwrap_or_default()
+    }
+
+    pub fn announcements(delegate: &T::AccountId) -> Vec<AnnouncementOf<T>> {
+        Announcements::<T>::get(delegate).unwrap_or_default()
+    }
+
+    /// The proxies `delegate` has for `real` whose type allows `call`.
+    fn find_proxies(
+        real: &T::AccountId,
+        delegate: &T::AccountId,
+        call: &T::RuntimeCall,
+    ) -> Result<Vec<ProxyDefinitionOf<T>>, &'static str> {
+        let proxies: Vec<_> = Self::proxies(real)
+            .into_iter()
+            .filter(|proxy| proxy.delegate == *delegate)
+            .collect();
+        if proxies.is_empty() {
+            return Err("Not a proxy");
+        }
+        let allowed: Vec<_> = proxies
+            .into_iter()
+            .filter(|proxy| proxy.proxy_type.filter(call))
+            .collect();
+        if allowed.is_empty() {
+            return Err("Call not allowed by proxy type");
+        }
+        Ok(allowed)
+    }
+
+    /// Remove the announcement of `delegate` for `call_hash` on behalf of `real`, if `ready`
+    /// accepts it.
+    fn take_announcement(
+        delegate: &T::AccountId,
+        real: &T::AccountId,
+        call_hash: H256,
+        ready: impl Fn(&AnnouncementOf<T>) -> bool,
+    ) -> Result<AnnouncementOf<T>, &'static str> {
+        let mut announcements = Self::announcements(delegate);
+        let index = announcements
+            .iter()
+            .position(|announcement| {
+                announcement.real == *real && announcement.call_hash == call_hash
+            })
+            .ok_or("Announcement not found")?;
+        if !ready(&announcements[index]) {
+            return Err("Announcement is not due yet");
+        }
+
+        let announcement = announcements.remove(index);
+        if announcements.is_empty() {
+            Announcements::<T>::remove(delegate);
+        } else {
+            Announcements::<T>::insert(delegate.clone(), announcements);
+        }
+        Ok(announcement)
+    }
+
+    /// Remove the announcements `delegate` made on behalf of `real`.
+    fn remove_announcements(delegate: &T::AccountId, real: &T::AccountId) {
+        Announcements::<T>::mutate(delegate, |announcements| {
+            if let Some(list) = announcements {
+                list.retain(|announcement| announcement.real != *real);
+                if list.is_empty() {
+                    *announcements = None;
+                }
+            }
+        });
+    }
+
+    fn do_proxy(real: T::AccountId, call: T::RuntimeCall) {
+        let result = transactional(|| call.dispatch(real));
+        system::Pallet::<T>::deposit_event(Event::ProxyExecuted { result });
+    }
+}
+
+impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        balances,
+        mock::{transfer, TestCall, TestConfig, TestEvent},
+        support::traits::ConstU32,
+    };
+
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub enum TestProxyType {
+        Any,
+        BalancesOnly,
+    }
+
+    impl Encode for TestProxyType {
+        fn encode_to(&self, dest: &mut Vec<u8>) {
+            dest.push(self.clone() as u8);
+        }
+    }
+
+    impl InstanceFilter<TestCall> for TestProxyType {
+        fn filter(&self, call: &TestCall) -> bool {
+            match self {
+                TestProxyType::Any => true,
+                TestProxyType::BalancesOnly => matches!(call, TestCall::Balances(_)),
+            }
+        }
+    }
+
+    impl Config for TestConfig {
+        type RuntimeCall = TestCall;
+        type ProxyType = TestProxyType;
+        type MaxProxies = ConstU32<2>;
+        type MaxPending = ConstU32<2>;
+    }
+
+    type Balances = balances::Pallet<TestConfig>;
+    type Proxy = Pallet<TestConfig>;
+
+    #[test]
+    fn proxy_dispatches_allowed_calls() {
+        let a = "A".to_string();
+        let b = "B".to_string();
+        Balances::set_balance(&a, 100);
+        let mut proxy = Proxy::new();
+
+        assert_eq!(
+            proxy.proxy(b.clone(), a.clone(), transfer("C", 10)),
+            Err("Not a proxy")
+        );
+        proxy
+            .add_proxy(a.clone(), b.clone(), TestProxyType::BalancesOnly, 0)
+            .unwrap();
+        assert_eq!(
+            proxy.add_proxy(a.clone(), b.clone(), TestProxyType::BalancesOnly, 0),
+            Err("Proxy already exists")
+        );
+
+        proxy
+            .proxy(b.clone(), a.clone(), transfer("C", 10))
+            .unwrap();
+        assert_eq!(Balances::balance(&a), 90);
+        assert_eq!(Balances::balance(&"C".to_string()), 10);
+        assert_eq!(
+            proxy.proxy(b.clone(), a.clone(), Box::new(TestCall::Remark)),
+            Err("Call not allowed by proxy type")
+        );
+
+        // The result of the proxied call is reported in an event.
+        proxy
+            .proxy(b.clone(), a.clone(), transfer("C", 1000))
+            .unwrap();
+        assert!(
+            system::Pallet::<TestConfig>::events().contains(&TestEvent::Proxy(
+                Event::ProxyExecuted {
+                    result: Err("Insufficient fund")
+                }
+            ))
+        );
+
+        proxy
+            .remove_proxy(a.clone(), b.clone(), TestProxyType::BalancesOnly, 0)
+            .unwrap();
+        assert_eq!(proxy.proxy(b, a, transfer("C", 10)), Err("Not a proxy"));
+    }
+
+    #[test]
+    fn delayed_proxy_must_announce() {
+        let a = "A".to_string();
+        let b = "B".to_string();
+        Balances::set_balance(&a, 100);
+        let mut proxy = Proxy::new();
+        proxy
+            .add_proxy(a.clone(), b.clone(), TestProxyType::Any, 2)
+            .unwrap();
+
+        assert_eq!(
+            proxy.proxy(b.clone(), a.clone(), transfer("C", 10)),
+            Err("Call must be announced first")
+        );
+        proxy
+            .announce(b.clone(), a.clone(), hash_of(&transfer("C", 10)))
+            .unwrap();
+        system::Pallet::<TestConfig>::inc_block_number();
+        assert_eq!(
+            proxy.proxy_announced("D".to_string(), b.clone(), a.clone(), transfer("C", 10)),
+            Err("Announcement is not due yet")
+        );
+
+        system::Pallet::<TestConfig>::inc_block_number();
+        proxy
+            .proxy_announced("D".to_string(), b.clone(), a.clone(), transfer("C", 10))
+            .unwrap();
+        assert_eq!(Balances::balance(&"C".to_string()), 10);
+        assert!(Proxy::announcements(&b).is_empty());
+    }
+
+    #[test]
+    fn real_account_can_reject_announcements() {
+        let a = "A".to_string();
+        let b = "B".to_string();
+        let mut proxy = Proxy::new();
+        proxy
+            .add_proxy(a.clone(), b.clone(), TestProxyType::Any, 1)
+            .unwrap();
+        let call_hash = hash_of(&transfer("C", 10));
+        proxy.announce(b.clone(), a.clone(), call_hash).unwrap();
+
+        proxy
+            .reject_announcement(a.clone(), b.clone(), call_hash)
+            .unwrap();
+        system::Pallet::<TestConfig>::inc_block_number();
+        assert_eq!(
+            proxy.proxy_announced(a.clone(), b, a, transfer("C", 10)),
+            Err("Announcement not found")
+        );
+    }
+
+    #[test]
+    fn remove_proxies_clears_their_announcements() {
+        let (a, b, c, d) = (
+            "A".to_string(),
+            "B".to_string(),
+            "C".to_string(),
+            "D".to_string(),
+        );
+        let mut proxy = Proxy::new();
+        proxy
+            .add_proxy(a.clone(), b.clone(), TestProxyType::Any, 1)
+            .unwrap();
+        proxy
+            .add_proxy(a.clone(), c.clone(), TestProxyType::BalancesOnly, 0)
+            .unwrap();
+        proxy
+            .add_proxy(d.clone(), b.clone(), TestProxyType::Any, 1)
+            .unwrap();
+        let call_hash = hash_of(&transfer("C", 10));
+        proxy.announce(b.clone(), a.clone(), call_hash).unwrap();
+        proxy.announce(b.clone(), d.clone(), call_hash).unwrap();
+        system::Pallet::<TestConfig>::reset_events();
+
+        proxy.remove_proxies(a.clone()).unwrap();
+        assert!(Proxy::proxies(&a).is_empty());
+        // Only the announcements made on behalf of A are removed.
+        assert_eq!(
+            Proxy::announcements(&b)
+                .into_iter()
+                .map(|announcement| announcement.real)
+                .collect::<Vec<_>>(),
+            vec![d]
+        );
+        assert_eq!(
+            system::Pallet::<TestConfig>::events(),
+            vec![
+                TestEvent::Proxy(Event::ProxyRemoved {
+                    delegator: a.clone(),
+                    delegate: b,
+                    proxy_type: TestProxyType::Any,
+                    delay: 1,
+                }),
+                TestEvent::Proxy(Event::ProxyRemoved {
+                    delegator: a,
+                    delegate: c,
+                    proxy_type: TestProxyType::BalancesOnly,
+                    delay: 0,
+                }),
+            ]
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use std::marker::PhantomData;
+
+use crate::{
+    support::{
+        codec::Encode,
+        hashing::{hash_of, H256},
+        storage::StorageValue,
+        traits::Hooks,
+        Weight,
+    },
+    system,
+};
+
+/// The number of blocks whose seeds are mixed into a random value.
+pub const RANDOM_MATERIAL_LEN: usize = 81;
+
+/// This pallet has no calls and deposits no events. A runtime includes it with a field marked
+/// `#[no_call]` and `#[no_event]`.
+pub trait Config: system::Config<BlockNumber: Encode> {}
+
+/// A seed for each of the last `RANDOM_MATERIAL_LEN` blocks, oldest first. Each seed is derived
+/// from the previous one and the block number.
+pub struct RandomMaterial<T>(PhantomData<T>);
+impl<T: Config> StorageValue for RandomMaterial<T> {
+    type Value = Vec<H256>;
+}
+
+/// Random values for other pallets, like picking a winner.
+///
+/// The values are known to anyone who knows the block number, so they must not be used where
+/// guessing them is worth something.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pallet<T: Config>(PhantomData<T>);
+
+impl<T: Config> Pallet<T> {
+    pub fn new() -> Self {
+        Pallet(PhantomData)
+    }
+
+    /// A random value for `subject`, which tells apart the values needed in the same block.
+    pub fn random(subject: &[u8]) -> H256 {
+        hash_of(&(subject, RandomMaterial::<T>::get().unwrap_or_default()))
+    }
+}
+
+impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
+    fn on_initialize(n: T::BlockNumber) -> Weight {
+        RandomMaterial::<T>::mutate(|material| {
+            let material = material.get_or_insert_with(Vec::new);
+            let parent = material.last().copied().unwrap_or_default();
+            material.push(hash_of(&(parent, n)));
+            if material.len() > RANDOM_MATERIAL_LEN {
+                material.remove(0);
+            }
+        });
+        0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mock::TestConfig;
+
+    use super::*;
+
+    impl Config for TestConfig {}
+
+    type Randomness = Pallet<TestConfig>;
+
+    #[test]
+    fn random_values_change_with_blocks_and_subjects() {
+        let before = Randomness::random(b"lottery");
+        Randomness::on_initialize(1);
+        let first = Randomness::random(b"lottery");
+        assert_ne!(first, before);
+        assert_ne!(first, Randomness::random(b"raffle"));
+
+        Randomness::on_initialize(2);
+        assert_ne!(Randomness::random(b"lottery"), first);
+
+        for n in 3..100 {
+            Randomness::on_initialize(n);
+        }
+        assert_eq!(
+            RandomMaterial::<TestConfig>::get().unwrap().len(),
+            RANDOM_MATERIAL_LEN
+        );
+    }
+}
@@ -0,0 +1,439 @@
+use std::marker::PhantomData;
+
+use num::traits::{CheckedAdd, One, Zero};
+
+use crate::{
+    support::{
+        storage::{transactional, StorageMap, StorageValue},
+        traits::{Currency, Get, Hooks, NamedReservableCurrency, ReserveIdentifier},
+        DispatchResult, Dispatchable, Weight,
+    },
+    system,
+};
+
+/// The name under which the deposits of scheduled tasks are reserved.
+pub const SCHEDULE_DEPOSIT_ID: ReserveIdentifier = *b"schedule";
+
+/// The number of blocks `do_schedule_earliest` looks through for an agenda with room.
+pub const SCHEDULE_EARLIEST_WINDOW: u32 = 256;
+
+/// Identifier of a scheduled task, unique over the lifetime of the chain.
+pub type TaskId = u32;
+
+pub type BalanceOf<T> =
+    <<T as Config>::Currency as Currency<<T as system::Config>::AccountId>>::Balance;
+
+pub trait Config: system::Config<RuntimeEvent: From<Event<Self>>> {
+    /// The calls that can be scheduled, usually the `RuntimeCall` of the runtime.
+    type RuntimeCall: Dispatchable<Caller = Self::AccountId> + Clone;
+    /// The currency task deposits are reserved in.
+    type Currency: NamedReservableCurrency<Self::AccountId, Balance: Clone + Zero>;
+    /// The amount reserved from the caller of `schedule` for each task, until the task runs for
+    /// the last time or is canceled. Tasks scheduled by other pallets take no deposit.
+    type ScheduleDeposit: Get<BalanceOf<Self>>;
+    /// The largest number of tasks dispatched in a single block.
+    type MaxScheduledPerBlock: Get<u32>;
+}
+
+/// A call waiting in the agenda of a block.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scheduled<AccountId, Balance, Call, BlockNumber> {
+    pub id: TaskId,
+    /// The account that scheduled the call, which it is dispatched as.
+    pub owner: AccountId,
+    /// The amount reserved from the owner, released once the task runs for the last time or is
+    /// canceled.
+    pub deposit: Balance,
+    pub call: Call,
+    /// The period of a repeating task, and how many more times it runs after this one.
+    pub maybe_periodic: Option<(BlockNumber, u32)>,
+}
+
+pub type ScheduledOf<T> = Scheduled<
+    <T as system::Config>::AccountId,
+    BalanceOf<T>,
+    <T as Config>::RuntimeCall,
+    <T as system::Config>::BlockNumber,
+>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event<T: Config> {
+    Scheduled {
+        id: TaskId,
+        when: T::BlockNumber,
+    },
+    Canceled {
+        id: TaskId,
+        when: T::BlockNumber,
+    },
+    /// A task was dispatched, with the given result.
+    Dispatched {
+        id: TaskId,
+        result: DispatchResult,
+    },
+    /// A repeating task could not be rescheduled and will not run again.
+    PeriodicFailed {
+        id: TaskId,
+    },
+}
+
+/// The tasks to dispatch at the start of each block, in the order they were scheduled.
+pub struct Agenda<T>(PhantomData<T>);
+impl<T: Config> StorageMap for Agenda<T> {
+    type Key = T::BlockNumber;
+    type Value = Vec<ScheduledOf<T>>;
+}
+
+/// The block each pending task is scheduled for.
+pub struct Lookup<T>(PhantomData<T>);
+impl<T: Config> StorageMap for Lookup<T> {
+    type Key = TaskId;
+    type Value = T::BlockNumber;
+}
+
+/// The id of the next task to be scheduled.
+pub struct NextTaskId<T>(PhantomData<T>);
+impl<T: Config> StorageValue for NextTaskId<T> {
+    type Value = TaskId;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pallet<T: Config>(PhantomData<T>);
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+    /// Dispatch `call` as the caller at the start of block `when`, and then every `period` blocks
+    /// for `count` more times if `maybe_periodic` is `Some((period, count))`.
+    ///
+    /// `ScheduleDeposit` is reserved from the caller until the task runs for the last time or is
+    /// canceled.
+    // The call is boxed because a `RuntimeCall` can contain a call of this pallet.
+    #[allow(clippy::boxed_local)]
+    pub fn schedule(
+        &mut self,
+        caller: T::AccountId,
+        when: T::BlockNumber,
+        maybe_periodic: Option<(T::BlockNumber, u32)>,
+        call: Box<T::RuntimeCall>,
+    ) -> DispatchResult {
+        if when <= system::Pallet::<T>::block_number() {
+            return Err("Target block must be in the future");
+        }
+        if maybe_periodic.is_some_and(|(period, _)| period.is_zero()) {
+            return Err("Period must not be zero");
+        }
+
+        let deposit = T::ScheduleDeposit::get();
+        // The deposit is only kept if the task fits in the agenda.
+        transactional(|| {
+            T::Currency::reserve_named(&SCHEDULE_DEPOSIT_ID, &caller, deposit.clone())?;
+            Self::add_task(caller, deposit, when, maybe_periodic, *call)
+        })?;
+        Ok(())
+    }
+
+    /// Cancel a task the caller scheduled.
+    pub fn cancel(&mut self, caller: T::AccountId, id: TaskId) -> DispatchResult {
+        let when = Lookup::<T>::get(&id).ok_or("Task not found")?;
+        let mut agenda = Agenda::<T>::get(&when).unwrap_or_default();
+        let index = agenda
+            .iter()
+            .position(|task| task.id == id)
+            .ok_or("Task not found")?;
+        if agenda[index].owner != caller {
+            return Err("Caller is not the owner of task");
+        }
+
+        let task = agenda.remove(index);
+        Self::set_agenda(when, agenda);
+        Lookup::<T>::remove(&id);
+        Self::release_deposit(&task);
+        system::Pallet::<T>::deposit_event(Event::Canceled { id, when });
+        Ok(())
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn new() -> Self {
+        Pallet(PhantomData)
+    }
+
+    pub fn agenda(when: &T::BlockNumber) -> Vec<ScheduledOf<T>> {
+        Agenda::<T>::get(when).unwrap_or_default()
+    }
+
+    /// Add a new task to the agenda of block `when`, without a deposit.
+    ///
+    /// Other pallets can use it to schedule calls of their own.
+    pub fn do_schedule(
+        owner: T::AccountId,
+        when: T::BlockNumber,
+        maybe_periodic: Option<(T::BlockNumber, u32)>,
+        call: T::RuntimeCall,
+    ) -> Result<TaskId, &'static str> {
+        Self::add_task(owner, BalanceOf::<T>::zero(), when, maybe_periodic, call)
+    }
+
+    /// Add a new task to the agenda of the first block from `earliest` with room for it, without
+    /// a deposit. Returns the id of the task and the block it runs in.
+    ///
+    /// Pallets use it for calls that must not be lost because the agenda of a block is full. Only
+    /// the `SCHEDULE_EARLIEST_WINDOW` blocks from `earliest` are looked through.
+    pub fn do_schedule_earliest(
+        owner: T::AccountId,
+        earliest: T::BlockNumber,
+        call: T::RuntimeCall,
+    ) -> Result<(TaskId, T::BlockNumber), &'static str> {
+        let max = T::MaxScheduledPerBlock::get() as usize;
+        if max == 0 {
+            return Err("Agenda is full");
+        }
+        let mut when = earliest;
+        for _ in 0..SCHEDULE_EARLIEST_WINDOW {
+            if Self::agenda(&when).len() < max {
+                let id = Self::do_schedule(owner, when, None, call)?;
+                return Ok((id, when));
+            }
+            when = when
+                .checked_add(&T::BlockNumber::one())
+                .ok_or("Block number overflow")?;
+        }
+        Err("No agenda with room in the scheduling window")
+    }
+
+    /// Add a new task to the agenda of block `when`, whose owner already reserved `deposit`.
+    fn add_task(
+        owner: T::AccountId,
+        deposit: BalanceOf<T>,
+        when: T::BlockNumber,
+        maybe_periodic: Option<(T::BlockNumber, u32)>,
+        call: T::RuntimeCall,
+    ) -> Result<TaskId, &'static str> {
+        let id = NextTaskId::<T>::get().unwrap_or(0);
+        Self::push_task(
+            when,
+            Scheduled {
+                id,
+                owner,
+                deposit,
+                call,
+                maybe_periodic,
+            },
+        )?;
+        NextTaskId::<T>::put(id.checked_add(1).ok_or("Task ids are exhausted")?);
+        system::Pallet::<T>::deposit_event(Event::Scheduled { id, when });
+        Ok(id)
+    }
+
+    fn push_task(when: T::BlockNumber, task: ScheduledOf<T>) -> DispatchResult {
+        let mut agenda = Self::agenda(&when);
+        if agenda.len() >= T::MaxScheduledPerBlock::get() as usize {
+            return Err("Agenda is full");
+        }
+
+        Lookup::<T>::insert(task.id, when);
+        agenda.push(task);
+        Agenda::<T>::insert(when, agenda);
+        Ok(())
+    }
+
+    /// Release the deposit of a task that will not run again.
+    fn release_deposit(task: &ScheduledOf<T>) {
+        if !task.deposit.is_zero() {
+            T::Currency::unreserve_named(&SCHEDULE_DEPOSIT_ID, &task.owner, task.deposit.clone());
+        }
+    }
+
+    fn set_agenda(when: T::BlockNumber, agenda: Vec<ScheduledOf<T>>) {
+        if agenda.is_empty() {
+            Agenda::<T>::remove(&when);
+        } else {
+            Agenda::<T>::insert(when, agenda);
+        }
+    }
+}
+
+impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
+    /// Dispatch the tasks scheduled for block `n`, and schedule the next run of repeating tasks.
+    ///
+    /// Each task weighs as much as an extrinsic.
+    fn on_initialize(n: T::BlockNumber) -> Weight {
+        let agenda = Agenda::<T>::remove(&n).unwrap_or_default();
+        let weight = <T as system::Config>::ExtrinsicBaseWeight::get() * agenda.len() as Weight;
+        // Every task of the agenda leaves the lookup before any is dispatched, so a task canceling
+        // another task of this block finds it gone instead of missing from the agenda.
+        for task in &agenda {
+            Lookup::<T>::remove(&task.id);
+        }
+        for task in agenda {
+            let result = transactional(|| task.call.clone().dispatch(task.owner.clone()));
+            system::Pallet::<T>::deposit_event(Event::Dispatched {
+                id: task.id,
+                result,
+            });
+
+            let Some((period, count)) = task.maybe_periodic.filter(|(_, count)| *count > 0) else {
+                Self::release_deposit(&task);
+                continue;
+            };
+            let next = Scheduled {
+                maybe_periodic: (count > 1).then_some((period, count - 1)),
+                ..task.clone()
+            };
+            let rescheduled = n
+                .checked_add(&period)
+                .ok_or("Block number overflow")
+                .and_then(|when| Self::push_task(when, next));
+            if rescheduled.is_err() {
+                Self::release_deposit(&task);
+                system::Pallet::<T>::deposit_event(Event::PeriodicFailed { id: task.id });
+            }
+        }
+        weight
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        balances,
+        mock::{transfer, TestCall, TestConfig, TestEvent},
+        support::traits::{ConstU128, ConstU32, ReservableCurrency},
+    };
+
+    use super::*;
+
+    impl Config for TestConfig {
+        type RuntimeCall = TestCall;
+        type Currency = balances::Pallet<TestConfig>;
+        type ScheduleDeposit = ConstU128<5>;
+        type MaxScheduledPerBlock = ConstU32<2>;
+    }
+
+    type Balances = balances::Pallet<TestConfig>;
+    type Scheduler = Pallet<TestConfig>;
+
+    #[test]
+    fn calls_run_at_their_block() {
+        let a = "A".to_string();
+        Balances::set_balance(&a, 100);
+        let mut scheduler = Scheduler::new();
+
+        assert_eq!(
+            scheduler.schedule(a.clone(), 0, None, transfer("B", 10)),
+            Err("Target block must be in the future")
+        );
+        scheduler
+            .schedule(a.clone(), 2, None, transfer("B", 10))
+            .unwrap();
+        scheduler
+            .schedule(a.clone(), 2, None, transfer("B", 1000))
+            .unwrap();
+        assert_eq!(
+            scheduler.schedule(a.clone(), 2, None, transfer("B", 10)),
+            Err("Agenda is full")
+        );
+        assert_eq!(Balances::reserved_balance(&a), 10);
+
+        Scheduler::on_initialize(1);
+        assert_eq!(Balances::balance(&"B".to_string()), 0);
+        Scheduler::on_initialize(2);
+        assert_eq!(Balances::balance(&"B".to_string()), 10);
+        assert_eq!(Balances::reserved_balance(&a), 0);
+        assert!(Scheduler::agenda(&2).is_empty());
+        let events = system::Pallet::<TestConfig>::events();
+        assert!(events.contains(&TestEvent::Scheduler(Event::Dispatched {
+            id: 1,
+            result: Err("Insufficient fund")
+        })));
+    }
+
+    #[test]
+    fn periodic_calls_repeat() {
+        let a = "A".to_string();
+        Balances::set_balance(&a, 100);
+        let mut scheduler = Scheduler::new();
+        scheduler
+            .schedule(a.clone(), 1, Some((3, 2)), transfer("B", 10))
+            .unwrap();
+
+        for n in 1..=6 {
+            Scheduler::on_initialize(n);
+        }
+        assert_eq!(Balances::reserved_balance(&a), 5);
+        for n in 7..=10 {
+            Scheduler::on_initialize(n);
+        }
+        // The call ran in blocks 1, 4 and 7.
+        assert_eq!(Balances::balance(&"B".to_string()), 30);
+        assert_eq!(Balances::reserved_balance(&a), 0);
+        assert!(Scheduler::agenda(&10).is_empty());
+    }
+
+    #[test]
+    fn owner_can_cancel() {
+        let a = "A".to_string();
+        Balances::set_balance(&a, 100);
+        let mut scheduler = Scheduler::new();
+        scheduler
+            .schedule(a.clone(), 1, Some((1, 5)), transfer("B", 10))
+            .unwrap();
+        Scheduler::on_initialize(1);
+
+        assert_eq!(
+            scheduler.cancel("B".to_string(), 0),
+            Err("Caller is not the owner of task")
+        );
+        scheduler.cancel(a.clone(), 0).unwrap();
+        assert_eq!(Balances::reserved_balance(&a), 0);
+        assert_eq!(scheduler.cancel(a, 0), Err("Task not found"));
+        Scheduler::on_initialize(2);
+        assert_eq!(Balances::balance(&"B".to_string()), 10);
+    }
+
+    #[test]
+    fn task_canceling_a_task_of_the_same_block() {
+        let a = "A".to_string();
+        Balances::set_balance(&a, 100);
+        let mut scheduler = Scheduler::new();
+        let cancel = TestCall::Scheduler(Call::cancel { id: 1 });
+        scheduler
+            .schedule(a.clone(), 1, None, Box::new(cancel))
+            .unwrap();
+        scheduler
+            .schedule(a.clone(), 1, None, transfer("B", 10))
+            .unwrap();
+
+        Scheduler::on_initialize(1);
+        // Both tasks were taken off the agenda before the first ran, so the second still runs.
+        assert_eq!(Balances::balance(&"B".to_string()), 10);
+        assert_eq!(Balances::reserved_balance(&a), 0);
+        let events = system::Pallet::<TestConfig>::events();
+        assert!(events.contains(&TestEvent::Scheduler(Event::Dispatched {
+            id: 0,
+            result: Err("Task not found")
+        })));
+    }
+
+    #[test]
+    fn earliest_schedule_skips_full_agendas() {
+        let a = "A".to_string();
+        for _ in 0..2 {
+            Scheduler::do_schedule(a.clone(), 3, None, *transfer("B", 10)).unwrap();
+        }
+
+        assert_eq!(
+            Scheduler::do_schedule_earliest(a.clone(), 3, *transfer("B", 10)),
+            Ok((2, 4))
+        );
+        for when in 5..5 + SCHEDULE_EARLIEST_WINDOW {
+            for _ in 0..2 {
+                Scheduler::do_schedule(a.clone(), when, None, *transfer("B", 10)).unwrap();
+            }
+        }
+        assert_eq!(
+            Scheduler::do_schedule_earliest(a, 5, *transfer("B", 10)),
+            Err("No agenda with room in the scheduling window")
+        );
+    }
+}
@@ -0,0 +1,550 @@
+use std::{fmt::Debug, marker::PhantomData};
+
+use num::traits::{CheckedAdd, Saturating, SaturatingMul, Unsigned, Zero};
+
+use crate::{
+    support::{
+        storage::{StorageMap, StorageValue},
+        traits::{Currency, Get, Hooks, LockIdentifier, LockableCurrency, WithdrawReasons},
+        DispatchResult, Weight,
+    },
+    system,
+};
+
+/// The name of the lock placed on bonded funds.
+pub const STAKING_ID: LockIdentifier = *b"staking ";
+
+/// The index of an era, counting from zero at genesis.
+pub type EraIndex = u32;
+
+pub type BalanceOf<T> =
+    <<T as Config>::Currency as Currency<<T as system::Config>::AccountId>>::Balance;
+
+pub trait Config: system::Config<RuntimeEvent: From<Event<Self>>> {
+    /// The currency funds are bonded in, usually provided by the balances pallet.
+    type Currency: LockableCurrency<
+        Self::AccountId,
+        Balance: Unsigned + Saturating + SaturatingMul + From<u32> + Copy + Ord + Debug,
+    >;
+    /// The number of blocks in an era.
+    type EraLength: Get<Self::BlockNumber>;
+    /// The number of validators elected for each era.
+    type ValidatorCount: Get<u32>;
+    /// The number of eras unbonded funds stay locked for.
+    type BondingDuration: Get<EraIndex>;
+    /// The amount minted at the end of each era and shared by its validators and nominators.
+    type RewardPerEra: Get<BalanceOf<Self>>;
+    /// The largest number of validators an account can nominate.
+    type MaxNominations: Get<u32>;
+}
+
+/// Funds being unbonded, which can be withdrawn from era `era` on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnlockChunk<Balance> {
+    pub value: Balance,
+    pub era: EraIndex,
+}
+
+/// The funds an account has bonded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StakingLedger<Balance> {
+    /// All bonded funds, including those being unbonded. It is the amount locked.
+    pub total: Balance,
+    /// The funds that count as stake in elections.
+    pub active: Balance,
+    pub unlocking: Vec<UnlockChunk<Balance>>,
+}
+
+/// The stake backing a validator in an era.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Exposure<AccountId, Balance> {
+    pub total: Balance,
+    /// The stake of the validator itself.
+    pub own: Balance,
+    /// The stake each nominator put behind the validator.
+    pub others: Vec<(AccountId, Balance)>,
+}
+
+pub type ExposureOf<T> = Exposure<<T as system::Config>::AccountId, BalanceOf<T>>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event<T: Config> {
+    Bonded {
+        stash: T::AccountId,
+        amount: BalanceOf<T>,
+    },
+    Unbonded {
+        stash: T::AccountId,
+        amount: BalanceOf<T>,
+    },
+    /// Unbonded funds were unlocked after the bonding duration.
+    Withdrawn {
+        stash: T::AccountId,
+        amount: BalanceOf<T>,
+    },
+    /// A new era started with the given validators.
+    NewEra {
+        era: EraIndex,
+        validators: Vec<T::AccountId>,
+    },
+    /// The staker was paid for backing a validator during the last era.
+    Rewarded {
+        stash: T::AccountId,
+        amount: BalanceOf<T>,
+    },
+}
+
+/// The bonded funds of each staker.
+pub struct Ledger<T>(PhantomData<T>);
+impl<T: Config> StorageMap for Ledger<T> {
+    type Key = T::AccountId;
+    type Value = StakingLedger<BalanceOf<T>>;
+}
+
+/// The stakers that want to be elected as validators.
+pub struct Validators<T>(PhantomData<T>);
+impl<T: Config> StorageMap for Validators<T> {
+    type Key = T::AccountId;
+    type Value = ();
+}
+
+/// The validators each nominator backs.
+pub struct Nominators<T>(PhantomData<T>);
+impl<T: Config> StorageMap for Nominators<T> {
+    type Key = T::AccountId;
+    type Value = Vec<T::AccountId>;
+}
+
+/// The current era.
+pub struct CurrentEra<T>(PhantomData<T>);
+impl<T: Config> StorageValue for CurrentEra<T> {
+    type Value = EraIndex;
+}
+
+/// The block in which the current era started.
+pub struct CurrentEraStart<T>(PhantomData<T>);
+impl<T: Config> StorageValue for CurrentEraStart<T> {
+    type Value = T::BlockNumber;
+}
+
+/// The validators elected for the current era, and the stake backing each of them.
+pub struct ErasStakers<T>(PhantomData<T>);
+impl<T: Config> StorageMap for ErasStakers<T> {
+    type Key = T::AccountId;
+    type Value = ExposureOf<T>;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pallet<T: Config>(PhantomData<T>);
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+    /// Bond `value` of the free balance of the caller.
+    pub fn bond(&mut self, caller: T::AccountId, value: BalanceOf<T>) -> DispatchResult {
+        if Ledger::<T>::contains_key(&caller) {
+            return Err("Account is already bonded");
+        }
+        if value.is_zero() {
+            return Err("Cannot bond nothing");
+        }
+        if value > T::Currency::free_balance(&caller) {
+            return Err("Insufficient free balance to bond");
+        }
+
+        Self::update_ledger(
+            &caller,
+            StakingLedger {
+                total: value,
+                active: value,
+                unlocking: Vec::new(),
+            },
+        );
+        system::Pallet::<T>::deposit_event(Event::Bonded {
+            stash: caller,
+            amount: value,
+        });
+        Ok(())
+    }
+
+    /// Bond up to `max_additional` more of the free balance of the caller.
+    pub fn bond_extra(
+        &mut self,
+        caller: T::AccountId,
+        max_additional: BalanceOf<T>,
+    ) -> DispatchResult {
+        let mut ledger = Self::ledger(&caller)?;
+        let extra = T::Currency::free_balance(&caller)
+            .saturating_sub(ledger.total)
+            .min(max_additional);
+        ledger.total = ledger.total.saturating_add(extra);
+        ledger.active = ledger.active.saturating_add(extra);
+
+        Self::update_ledger(&caller, ledger);
+        system::Pallet::<T>::deposit_event(Event::Bonded {
+            stash: caller,
+            amount: extra,
+        });
+        Ok(())
+    }
+
+    /// Stop counting `value` of the bonded funds as stake. They can be withdrawn once
+    /// `BondingDuration` eras have passed.
+    pub fn unbond(&mut self, caller: T::AccountId, value: BalanceOf<T>) -> DispatchResult {
+        let mut ledger = Self::ledger(&caller)?;
+        if value > ledger.active {
+            return Err("Cannot unbond more than the active stake");
+        }
+        ledger.active = ledger.active.saturating_sub(value);
+        let era = Self::current_era()
+            .checked_add(T::BondingDuration::get())
+            .ok_or("Era index overflow")?;
+        ledger.unlocking.push(UnlockChunk { value, era });
+
+        Self::update_ledger(&caller, ledger);
+        system::Pallet::<T>::deposit_event(Event::Unbonded {
+            stash: caller,
+            amount: value,
+        });
+        Ok(())
+    }
+
+    /// Unlock the unbonded funds of the caller whose bonding duration has passed.
+    ///
+    /// Once nothing is bonded anymore, the caller stops staking altogether.
+    pub fn withdraw_unbonded(&mut self, caller: T::AccountId) -> DispatchResult {
+        let mut ledger = Self::ledger(&caller)?;
+        let current_era = Self::current_era();
+        let mut withdrawn = BalanceOf::<T>::zero();
+        ledger.unlocking.retain(|chunk| {
+            if chunk.era > current_era {
+                return true;
+            }
+            withdrawn = withdrawn.saturating_add(chunk.value);
+            false
+        });
+        ledger.total = ledger.total.saturating_sub(withdrawn);
+
+        if ledger.total.is_zero() {
+            Ledger::<T>::remove(&caller);
+            T::Currency::remove_lock(STAKING_ID, &caller);
+            Self::do_chill(&caller);
+        } else {
+            Self::update_ledger(&caller, ledger);
+        }
+        system::Pallet::<T>::deposit_event(Event::Withdrawn {
+            stash: caller,
+            amount: withdrawn,
+        });
+        Ok(())
+    }
+
+    /// Stand for election as a validator from the next era on.
+    pub fn validate(&mut self, caller: T::AccountId) -> DispatchResult {
+        Self::ledger(&caller)?;
+        Self::do_chill(&caller);
+        Validators::<T>::insert(caller, ());
+        Ok(())
+    }
+
+    /// Back `targets` with the active stake of the caller from the next era on.
+    pub fn nominate(&mut self, caller: T::AccountId, targets: Vec<T::AccountId>) -> DispatchResult {
+        Self::ledger(&caller)?;
+        if targets.is_empty() {
+            return Err("Must nominate at least one validator");
+        }
+        if targets.len() > T::MaxNominations::get() as usize {
+            return Err("Too many nominations");
+        }
+
+        Self::do_chill(&caller);
+        Nominators::<T>::insert(caller, targets);
+        Ok(())
+    }
+
+    /// Stop validating or nominating from the next era on. The funds stay bonded.
+    pub fn chill(&mut self, caller: T::AccountId) -> DispatchResult {
+        Self::ledger(&caller)?;
+        Self::do_chill(&caller);
+        Ok(())
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn new() -> Self {
+        Pallet(PhantomData)
+    }
+
+    pub fn current_era() -> EraIndex {
+        CurrentEra::<T>::get().unwrap_or(0)
+    }
+
+    pub fn ledger(who: &T::AccountId) -> Result<StakingLedger<BalanceOf<T>>, &'static str> {
+        Ledger::<T>::get(who).ok_or("Account is not bonded")
+    }
+
+    /// The validators of the current era, in the order they were elected.
+    pub fn validators() -> Vec<T::AccountId> {
+        let mut stakers = ErasStakers::<T>::iter();
+        Self::sort_by_stake(&mut stakers);
+        stakers.into_iter().map(|(who, _)| who).collect()
+    }
+
+    /// The stake backing `validator` in the current era, if it was elected.
+    pub fn exposure(validator: &T::AccountId) -> Option<ExposureOf<T>> {
+        ErasStakers::<T>::get(validator)
+    }
+
+    /// Store the ledger of `who` and lock its bonded funds against any withdrawal.
+    fn update_ledger(who: &T::AccountId, ledger: StakingLedger<BalanceOf<T>>) {
+        T::Currency::set_lock(STAKING_ID, who, ledger.total, WithdrawReasons::All);
+        Ledger::<T>::insert(who.clone(), ledger);
+    }
+
+    fn do_chill(who: &T::AccountId) {
+        Validators::<T>::remove(who);
+        Nominators::<T>::remove(who);
+    }
+
+    fn sort_by_stake(stakers: &mut [(T::AccountId, ExposureOf<T>)]) {
+        stakers.sort_by(|(a, a_exposure), (b, b_exposure)| {
+            b_exposure.total.cmp(&a_exposure.total).then(a.cmp(b))
+        });
+    }
+
+    /// Pay the reward of the current era to its validators and their nominators, in proportion
+    /// to their stake.
+    fn payout_era() {
+        let stakers = ErasStakers::<T>::iter();
+        if stakers.is_empty() {
+            return;
+        }
+        let share = T::RewardPerEra::get() / BalanceOf::<T>::from(stakers.len() as u32);
+        for (validator, exposure) in stakers {
+            if exposure.total.is_zero() {
+                continue;
+            }
+            let payees = std::iter::once((validator, exposure.own)).chain(exposure.others);
+            for (stash, stake) in payees {
+                let amount = share.saturating_mul(&stake) / exposure.total;
+                if amount.is_zero() {
+                    continue;
+                }
+                T::Currency::deposit_creating(&stash, amount);
+                system::Pallet::<T>::deposit_event(Event::Rewarded { stash, amount });
+            }
+        }
+    }
+
+    /// Elect the `ValidatorCount` candidates with the most stake behind them.
+    ///
+    /// Each nominator splits its active stake evenly between the targets that stand for
+    /// election.
+    fn elect() -> Vec<(T::AccountId, ExposureOf<T>)> {
+        let mut candidates: Vec<_> = Validators::<T>::iter()
+            .into_iter()
+            .map(|(who, ())| {
+                let own = Ledger::<T>::get(&who)
+                    .map(|ledger| ledger.active)
+                    .unwrap_or_else(BalanceOf::<T>::zero);
+                let exposure = Exposure {
+                    total: own,
+                    own,
+                    others: Vec::new(),
+                };
+                (who, exposure)
+            })
+            .collect();
+
+        for (nominator, targets) in Nominators::<T>::iter() {
+            let Some(ledger) = Ledger::<T>::get(&nominator) else {
+                continue;
+            };
+            let mut backed: Vec<_> = candidates
+                .iter_mut()
+                .filter(|(who, _)| targets.contains(who))
+                .collect();
+            if backed.is_empty() {
+                continue;
+            }
+            let stake = ledger.active / BalanceOf::<T>::from(backed.len() as u32);
+            if stake.is_zero() {
+                continue;
+            }
+            for (_, exposure) in backed.iter_mut() {
+                exposure.total = exposure.total.saturating_add(stake);
+                exposure.others.push((nominator.clone(), stake));
+            }
+        }
+
+        candidates.retain(|(_, exposure)| !exposure.total.is_zero());
+        Self::sort_by_stake(&mut candidates);
+        candidates.truncate(T::ValidatorCount::get() as usize);
+        candidates
+    }
+
+    /// End the current era and start the next one with newly elected validators.
+    fn rotate_era(n: T::BlockNumber) {
+        Self::payout_era();
+        ErasStakers::<T>::clear();
+
+        // Rotating cannot fail, so the last era lasts forever once era indices run out.
+        let era = Self::current_era().saturating_add(1);
+        let elected = Self::elect();
+        let validators = elected.iter().map(|(who, _)| who.clone()).collect();
+        for (who, exposure) in elected {
+            ErasStakers::<T>::insert(who, exposure);
+        }
+        CurrentEra::<T>::put(era);
+        CurrentEraStart::<T>::put(n);
+        system::Pallet::<T>::deposit_event(Event::NewEra { era, validators });
+    }
+}
+
+impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
+    /// Start a new era once the current one has lasted `EraLength` blocks.
+    ///
+    /// Rotating weighs as much as an extrinsic per validator and nominator taking part.
+    fn on_initialize(n: T::BlockNumber) -> Weight {
+        let start = CurrentEraStart::<T>::get().unwrap_or_else(T::BlockNumber::zero);
+        let Some(next) = start.checked_add(&T::EraLength::get()) else {
+            return 0;
+        };
+        if n < next {
+            return 0;
+        }
+
+        let stakers = Validators::<T>::iter().len() + Nominators::<T>::iter().len();
+        Self::rotate_era(n);
+        <T as system::Config>::ExtrinsicBaseWeight::get() * stakers as Weight
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        balances,
+        mock::TestConfig,
+        support::traits::{ConstU128, ConstU32},
+        types,
+    };
+
+    use super::*;
+
+    impl Config for TestConfig {
+        type Currency = balances::Pallet<TestConfig>;
+        type EraLength = ConstU32<5>;
+        type ValidatorCount = ConstU32<2>;
+        type BondingDuration = ConstU32<2>;
+        type RewardPerEra = ConstU128<100>;
+        type MaxNominations = ConstU32<2>;
+    }
+
+    type Balances = balances::Pallet<TestConfig>;
+    type Staking = Pallet<TestConfig>;
+
+    fn run_to_block(n: types::BlockNumber) {
+        let mut block = system::Pallet::<TestConfig>::block_number();
+        while block < n {
+            system::Pallet::<TestConfig>::inc_block_number();
+            block = system::Pallet::<TestConfig>::block_number();
+            Staking::on_initialize(block);
+        }
+    }
+
+    fn bonded(who: &str, amount: u128) -> types::AccountId {
+        let who = who.to_string();
+        Balances::set_balance(&who, 1_000);
+        Staking::new().bond(who.clone(), amount).unwrap();
+        who
+    }
+
+    #[test]
+    fn top_staked_validators_are_elected_and_rewarded() {
+        let mut staking = Staking::new();
+        let a = bonded("A", 100);
+        let b = bonded("B", 50);
+        let c = bonded("C", 10);
+        let d = bonded("D", 100);
+        staking.validate(a.clone()).unwrap();
+        staking.validate(b.clone()).unwrap();
+        staking.validate(c.clone()).unwrap();
+        assert_eq!(
+            staking.nominate(d.clone(), vec![]),
+            Err("Must nominate at least one validator")
+        );
+        staking.nominate(d.clone(), vec![c.clone()]).unwrap();
+
+        run_to_block(5);
+        assert_eq!(Staking::current_era(), 1);
+        assert_eq!(Staking::validators(), vec![c.clone(), a.clone()]);
+        assert_eq!(Staking::exposure(&c).unwrap().total, 110);
+
+        run_to_block(10);
+        assert_eq!(Staking::current_era(), 2);
+        assert_eq!(Balances::balance(&a), 1_050);
+        assert_eq!(Balances::balance(&b), 1_000);
+        // C and D share the reward of C in proportion to their stake.
+        assert_eq!(Balances::balance(&c), 1_004);
+        assert_eq!(Balances::balance(&d), 1_045);
+    }
+
+    #[test]
+    fn unbonded_funds_stay_locked_for_bonding_duration() {
+        let mut staking = Staking::new();
+        let a = bonded("A", 1_000);
+        let mut balances = Balances::new();
+        assert_eq!(
+            balances.transfer(a.clone(), "B".to_string(), 1),
+            Err("Liquidity restrictions prevent withdrawal")
+        );
+
+        assert_eq!(
+            staking.unbond(a.clone(), 1_001),
+            Err("Cannot unbond more than the active stake")
+        );
+        staking.unbond(a.clone(), 400).unwrap();
+        run_to_block(5);
+        staking.withdraw_unbonded(a.clone()).unwrap();
+        assert_eq!(Staking::ledger(&a).unwrap().total, 1_000);
+
+        run_to_block(10);
+        staking.withdraw_unbonded(a.clone()).unwrap();
+        assert_eq!(Staking::ledger(&a).unwrap().total, 600);
+        balances.transfer(a.clone(), "B".to_string(), 400).unwrap();
+
+        staking.unbond(a.clone(), 600).unwrap();
+        run_to_block(20);
+        staking.withdraw_unbonded(a.clone()).unwrap();
+        assert_eq!(Staking::ledger(&a), Err("Account is not bonded"));
+        assert!(Balances::locks(&a).is_empty());
+    }
+
+    #[test]
+    fn bonding_is_limited_by_free_balance() {
+        let mut staking = Staking::new();
+        let a = bonded("A", 600);
+        assert_eq!(
+            staking.bond(a.clone(), 100),
+            Err("Account is already bonded")
+        );
+        staking.bond_extra(a.clone(), 1_000).unwrap();
+        assert_eq!(Staking::ledger(&a).unwrap().active, 1_000);
+
+        Balances::set_balance(&"B".to_string(), 10);
+        assert_eq!(
+            staking.bond("B".to_string(), 11),
+            Err("Insufficient free balance to bond")
+        );
+        assert_eq!(
+            staking.nominate("B".to_string(), vec![a]),
+            Err("Account is not bonded")
+        );
+    }
+
+    #[test]
+    fn last_era_does_not_overflow() {
+        CurrentEra::<TestConfig>::put(EraIndex::MAX);
+        run_to_block(5);
+        assert_eq!(Staking::current_era(), EraIndex::MAX);
+    }
+}
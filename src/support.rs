@@ -1,3 +1,10 @@
+pub mod codec;
+pub mod hashing;
+pub mod instances;
+pub mod merkle;
+pub mod storage;
+pub mod traits;
+
 pub struct Header<BlockNumber> {
     pub block_number: BlockNumber,
 }
@@ -7,16 +14,42 @@ pub struct Extrinsic<Caller, Call> {
     pub call: Call,
 }
 
-pub struct Block<Header, Extrinsic> {
+pub struct Block<Header, Inherent, Extrinsic> {
     pub header: Header,
+    /// The calls the block author includes without signing them, like the block timestamp.
+    /// They are dispatched before any extrinsic.
+    pub inherents: Vec<Inherent>,
     pub extrinsics: Vec<Extrinsic>,
 }
 
 pub type DispatchResult = Result<(), &'static str>;
 
+/// The amount of computation a block spends on some work. A block can only hold a limited
+/// amount of it.
+pub type Weight = u64;
+
 pub trait Dispatch {
     type Caller;
     type Call;
 
     fn dispatch(&mut self, caller: Self::Caller, call: Self::Call) -> DispatchResult;
-}
\ No newline at end of file
+}
+
+/// Dispatching calls included by the block author rather than signed by an account.
+///
+/// `macros::call` implements it for the functions marked `#[inherent]`, which take no `caller`.
+pub trait DispatchInherent {
+    type Call;
+
+    fn dispatch_inherent(&mut self, call: Self::Call) -> DispatchResult;
+}
+
+/// A call that carries everything needed to dispatch it, so pallets can dispatch calls of other
+/// pallets without access to the runtime.
+///
+/// `macros::runtime` implements it for the `RuntimeCall` enum.
+pub trait Dispatchable {
+    type Caller;
+
+    fn dispatch(self, caller: Self::Caller) -> DispatchResult;
+}
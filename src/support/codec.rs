@@ -0,0 +1,148 @@
+/// A value with a byte encoding that does not depend on the build of the runtime.
+///
+/// Integers are written little-endian, sequences and strings are prefixed with their length as a
+/// `u32`, and composite values are the concatenation of their fields. Anything derived from a
+/// value and kept across runs, like an account id or a call hash, is computed over this encoding.
+pub trait Encode {
+    /// Append the encoding of `self` to `dest`.
+    fn encode_to(&self, dest: &mut Vec<u8>);
+
+    fn encode(&self) -> Vec<u8> {
+        let mut dest = Vec::new();
+        self.encode_to(&mut dest);
+        dest
+    }
+}
+
+macro_rules! impl_encode_for_int {
+    ($($int:ty),*) => {
+        $(
+            impl Encode for $int {
+                fn encode_to(&self, dest: &mut Vec<u8>) {
+                    dest.extend_from_slice(&self.to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_encode_for_int!(u8, u16, u32, u64, u128);
+
+impl Encode for bool {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        dest.push(*self as u8);
+    }
+}
+
+impl Encode for () {
+    fn encode_to(&self, _dest: &mut Vec<u8>) {}
+}
+
+/// Write the length of a sequence, which must fit in a `u32`.
+fn encode_len(len: usize, dest: &mut Vec<u8>) {
+    u32::try_from(len)
+        .expect("sequences are shorter than u32::MAX")
+        .encode_to(dest);
+}
+
+impl<T: Encode> Encode for [T] {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        encode_len(self.len(), dest);
+        for item in self {
+            item.encode_to(dest);
+        }
+    }
+}
+
+impl<T: Encode> Encode for Vec<T> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.as_slice().encode_to(dest);
+    }
+}
+
+/// Arrays have a fixed length, so it is not written.
+impl<T: Encode, const N: usize> Encode for [T; N] {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        for item in self {
+            item.encode_to(dest);
+        }
+    }
+}
+
+impl Encode for str {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.as_bytes().encode_to(dest);
+    }
+}
+
+impl Encode for String {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.as_str().encode_to(dest);
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for &T {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        (**self).encode_to(dest);
+    }
+}
+
+impl<T: Encode + ?Sized> Encode for Box<T> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        (**self).encode_to(dest);
+    }
+}
+
+impl<T: Encode> Encode for Option<T> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        match self {
+            None => dest.push(0),
+            Some(value) => {
+                dest.push(1);
+                value.encode_to(dest);
+            }
+        }
+    }
+}
+
+macro_rules! impl_encode_for_tuple {
+    ($(($($name:ident),+)),*) => {
+        $(
+            impl<$($name: Encode),+> Encode for ($($name,)+) {
+                #[allow(non_snake_case)]
+                fn encode_to(&self, dest: &mut Vec<u8>) {
+                    let ($($name,)+) = self;
+                    $( $name.encode_to(dest); )+
+                }
+            }
+        )*
+    };
+}
+
+impl_encode_for_tuple!((A), (A, B), (A, B, C), (A, B, C, D), (A, B, C, D, E));
+
+#[cfg(test)]
+mod test {
+    use super::Encode;
+
+    #[test]
+    fn integers_are_little_endian() {
+        assert_eq!(1u32.encode(), vec![1, 0, 0, 0]);
+        assert_eq!(0x0102u16.encode(), vec![2, 1]);
+    }
+
+    #[test]
+    fn sequences_are_length_prefixed() {
+        assert_eq!("ab".encode(), vec![2, 0, 0, 0, b'a', b'b']);
+        assert_eq!(vec![1u8, 2].encode(), vec![2, 0, 0, 0, 1, 2]);
+        assert_eq!(b"ab".encode(), vec![b'a', b'b']);
+        // The prefix keeps neighbouring fields apart.
+        assert_ne!(("a", "bc").encode(), ("ab", "c").encode());
+    }
+
+    #[test]
+    fn options_are_tagged() {
+        assert_eq!(None::<u8>.encode(), vec![0]);
+        assert_eq!(Some(7u8).encode(), vec![1, 7]);
+    }
+}
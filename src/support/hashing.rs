@@ -0,0 +1,24 @@
+use sha2::{Digest, Sha256};
+
+use super::codec::Encode;
+
+/// A 256-bit hash.
+pub type H256 = [u8; 32];
+
+/// The SHA-256 hash of `data`.
+pub fn sha2_256(data: &[u8]) -> H256 {
+    Sha256::digest(data).into()
+}
+
+/// The SHA-256 hash of the encoding of `value`, such as a `RuntimeCall`.
+///
+/// The encoding is stable across builds of the runtime, so the hash can be kept, for instance to
+/// derive an account id from it.
+pub fn hash_of<V: Encode + ?Sized>(value: &V) -> H256 {
+    sha2_256(&value.encode())
+}
+
+/// The lowercase hexadecimal representation of `bytes`.
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
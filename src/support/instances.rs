@@ -0,0 +1,10 @@
+/// The instances of a pallet that can be included more than once in a runtime, next to its
+/// default instance `()`.
+///
+/// Storage items are keyed by their type, which includes the instance, so every instance of a
+/// pallet has its own storage.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instance1;
+#[allow(dead_code)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Instance2;
@@ -0,0 +1,122 @@
+//! Binary Merkle trees over document hashes, built off-chain so that a single claim on the root
+//! proves the existence of every document in the tree.
+
+use super::hashing::{sha2_256, H256};
+
+/// A node next to the path from a leaf to the root, and the side it is on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sibling {
+    Left(H256),
+    Right(H256),
+}
+
+/// The siblings on the path from a leaf to the root, starting at the leaf.
+#[allow(dead_code)]
+pub type MerkleProof = Vec<Sibling>;
+
+// Leaves and inner nodes are hashed with different prefixes, so an inner node can never be
+// passed off as a leaf.
+fn hash_leaf(leaf: &H256) -> H256 {
+    sha2_256(&[&[0u8][..], leaf].concat())
+}
+
+fn hash_node(left: &H256, right: &H256) -> H256 {
+    sha2_256(&[&[1u8][..], left, right].concat())
+}
+
+/// Every level of the tree over `leaves`, from the hashed leaves up to the root.
+///
+/// A node without a sibling is moved up to the next level as it is.
+#[allow(dead_code)]
+fn levels(leaves: &[H256]) -> Vec<Vec<H256>> {
+    let mut levels = vec![leaves.iter().map(hash_leaf).collect::<Vec<_>>()];
+    while levels.last().expect("there is at least one level").len() > 1 {
+        let level = levels.last().expect("there is at least one level");
+        let next = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [left, right] => hash_node(left, right),
+                [single] => *single,
+                _ => unreachable!("chunks have one or two nodes"),
+            })
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+/// The root of the tree over `leaves`, or `None` if there are no leaves.
+#[allow(dead_code)]
+pub fn merkle_root(leaves: &[H256]) -> Option<H256> {
+    levels(leaves).last()?.first().copied()
+}
+
+/// The proof that the leaf at `index` is part of the tree over `leaves`.
+#[allow(dead_code)]
+pub fn merkle_proof(leaves: &[H256], index: usize) -> Option<MerkleProof> {
+    if index >= leaves.len() {
+        return None;
+    }
+
+    let mut proof = Vec::new();
+    let mut index = index;
+    for level in levels(leaves) {
+        let sibling = if index.is_multiple_of(2) {
+            level.get(index + 1).copied().map(Sibling::Right)
+        } else {
+            Some(Sibling::Left(level[index - 1]))
+        };
+        proof.extend(sibling);
+        index /= 2;
+    }
+    Some(proof)
+}
+
+/// Whether `proof` shows that `leaf` is part of the tree with the given `root`.
+pub fn verify_proof(root: &H256, leaf: &H256, proof: &[Sibling]) -> bool {
+    let computed = proof
+        .iter()
+        .fold(hash_leaf(leaf), |node, sibling| match sibling {
+            Sibling::Left(left) => hash_node(left, &node),
+            Sibling::Right(right) => hash_node(&node, right),
+        });
+    computed == *root
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn leaves(count: u8) -> Vec<H256> {
+        (0..count).map(|i| sha2_256(&[i])).collect()
+    }
+
+    #[test]
+    fn proofs_verify_for_every_leaf() {
+        for count in 1..=9 {
+            let leaves = leaves(count);
+            let root = merkle_root(&leaves).unwrap();
+            for (index, leaf) in leaves.iter().enumerate() {
+                let proof = merkle_proof(&leaves, index).unwrap();
+                assert!(verify_proof(&root, leaf, &proof));
+            }
+            assert_eq!(merkle_proof(&leaves, leaves.len()), None);
+        }
+        assert_eq!(merkle_root(&[]), None);
+    }
+
+    #[test]
+    fn invalid_proofs_are_rejected() {
+        let leaves = leaves(5);
+        let root = merkle_root(&leaves).unwrap();
+        let proof = merkle_proof(&leaves, 2).unwrap();
+
+        assert!(!verify_proof(&root, &leaves[3], &proof));
+        assert!(!verify_proof(&root, &sha2_256(b"other"), &proof));
+        assert!(!verify_proof(&root, &leaves[2], &proof[1..]));
+        // An inner node is not accepted as a leaf.
+        let inner = hash_node(&hash_leaf(&leaves[0]), &hash_leaf(&leaves[1]));
+        let proof = merkle_proof(&leaves, 0).unwrap();
+        assert!(!verify_proof(&root, &inner, &proof[1..]));
+    }
+}
@@ -0,0 +1,271 @@
+use std::{
+    any::{Any, TypeId},
+    cell::RefCell,
+    collections::BTreeMap,
+    rc::Rc,
+};
+
+/// The state of every storage item, keyed by the type that declares the item.
+type Items = BTreeMap<TypeId, Rc<dyn Any>>;
+
+/// The storage backend shared by all pallets of a runtime.
+///
+/// Each storage item is kept behind an `Rc`, so opening a transaction only copies pointers. An
+/// item is cloned the first time it is written while a snapshot still refers to it.
+#[derive(Default)]
+struct Backend {
+    items: Items,
+    /// The state at the start of each open transaction, innermost last.
+    transactions: Vec<Items>,
+}
+
+thread_local! {
+    static BACKEND: RefCell<Backend> = RefCell::new(Backend::default());
+}
+
+/// Read the item declared by `S`, if it was ever written.
+fn read<S: ?Sized + 'static, D: 'static, R>(f: impl FnOnce(Option<&D>) -> R) -> R {
+    BACKEND.with(|backend| {
+        let backend = backend.borrow();
+        let item = backend.items.get(&TypeId::of::<S>());
+        f(item.map(|item| {
+            item.downcast_ref::<D>()
+                .expect("storage item has a single type")
+        }))
+    })
+}
+
+/// Write the item declared by `S`, creating it with `D::default()` if needed.
+fn write<S: ?Sized + 'static, D: Clone + Default + 'static, R>(f: impl FnOnce(&mut D) -> R) -> R {
+    BACKEND.with(|backend| {
+        let mut backend = backend.borrow_mut();
+        let item = backend
+            .items
+            .entry(TypeId::of::<S>())
+            .or_insert_with(|| Rc::new(D::default()));
+        if Rc::get_mut(item).is_none() {
+            // The item is shared with a transaction snapshot, so we copy it before writing.
+            let copy = item
+                .downcast_ref::<D>()
+                .expect("storage item has a single type")
+                .clone();
+            *item = Rc::new(copy);
+        }
+        let item = Rc::get_mut(item).expect("item was just made unique");
+        f(item
+            .downcast_mut::<D>()
+            .expect("storage item has a single type"))
+    })
+}
+
+/// A single value in storage.
+///
+/// Implement this on a marker type to declare a storage item. The marker type is what tells
+/// items apart, so every item needs its own type.
+pub trait StorageValue: 'static {
+    type Value: Clone + 'static;
+
+    fn get() -> Option<Self::Value> {
+        read::<Self, Option<Self::Value>, _>(|value| value.cloned().flatten())
+    }
+    fn exists() -> bool {
+        Self::get().is_some()
+    }
+
+    fn put(value: Self::Value) {
+        write::<Self, Option<Self::Value>, _>(|item| *item = Some(value));
+    }
+
+    fn kill() -> Option<Self::Value> {
+        write::<Self, Option<Self::Value>, _>(|item| item.take())
+    }
+
+    /// Update the value with `f`, where `None` means the value is absent.
+    ///
+    /// The value is copied out of storage while `f` runs, so `f` is free to access other storage
+    /// items.
+    fn mutate<R>(f: impl FnOnce(&mut Option<Self::Value>) -> R) -> R {
+        let mut value = Self::get();
+        let result = f(&mut value);
+        match value {
+            Some(value) => Self::put(value),
+            None => {
+                Self::kill();
+            }
+        }
+        result
+    }
+}
+
+/// A map from keys to values in storage.
+///
+/// Implement this on a marker type to declare a storage item, like for `StorageValue`.
+pub trait StorageMap: 'static {
+    type Key: Ord + Clone + 'static;
+    type Value: Clone + 'static;
+
+    fn get(key: &Self::Key) -> Option<Self::Value> {
+        read::<Self, BTreeMap<Self::Key, Self::Value>, _>(|map| map?.get(key).cloned())
+    }
+    fn contains_key(key: &Self::Key) -> bool {
+        read::<Self, BTreeMap<Self::Key, Self::Value>, _>(|map| {
+            map.is_some_and(|map| map.contains_key(key))
+        })
+    }
+
+    fn insert(key: Self::Key, value: Self::Value) {
+        write::<Self, BTreeMap<Self::Key, Self::Value>, _>(|map| map.insert(key, value));
+    }
+
+    fn remove(key: &Self::Key) -> Option<Self::Value> {
+        write::<Self, BTreeMap<Self::Key, Self::Value>, _>(|map| map.remove(key))
+    }
+
+    /// Update the value at `key` with `f`, where `None` means the key is absent.
+    ///
+    /// The value is copied out of storage while `f` runs, so `f` is free to access other storage
+    /// items.
+    fn mutate<R>(key: &Self::Key, f: impl FnOnce(&mut Option<Self::Value>) -> R) -> R {
+        let mut value = Self::get(key);
+        let result = f(&mut value);
+        match value {
+            Some(value) => Self::insert(key.clone(), value),
+            None => {
+                Self::remove(key);
+            }
+        }
+        result
+    }
+
+    /// All entries of the map, in key order.
+    fn iter() -> Vec<(Self::Key, Self::Value)> {
+        read::<Self, BTreeMap<Self::Key, Self::Value>, _>(|map| {
+            map.into_iter()
+                .flatten()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect()
+        })
+    }
+    fn clear() {
+        write::<Self, BTreeMap<Self::Key, Self::Value>, _>(|map| map.clear());
+    }
+}
+
+/// Open a new transactional layer. Every change made until the matching `commit_transaction` or
+/// `rollback_transaction` can be discarded at once.
+pub fn start_transaction() {
+    BACKEND.with(|backend| {
+        let mut backend = backend.borrow_mut();
+        let snapshot = backend.items.clone();
+        backend.transactions.push(snapshot);
+    });
+}
+
+/// Keep the changes made in the innermost transactional layer.
+pub fn commit_transaction() {
+    BACKEND.with(|backend| {
+        backend
+            .borrow_mut()
+            .transactions
+            .pop()
+            .expect("no transaction to commit");
+    });
+}
+
+/// Discard the changes made in the innermost transactional layer.
+pub fn rollback_transaction() {
+    BACKEND.with(|backend| {
+        let mut backend = backend.borrow_mut();
+        let snapshot = backend
+            .transactions
+            .pop()
+            .expect("no transaction to roll back");
+        backend.items = snapshot;
+    });
+}
+
+/// Run `f` in a new transactional layer, keeping its changes only if it returns `Ok`.
+///
+/// Layers can be nested: rolling back an inner layer leaves the changes of the outer layers in
+/// place, and rolling back an outer layer also discards everything its inner layers committed.
+pub fn transactional<R, E>(f: impl FnOnce() -> Result<R, E>) -> Result<R, E> {
+    start_transaction();
+    let result = f();
+    match result {
+        Ok(_) => commit_transaction(),
+        Err(_) => rollback_transaction(),
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Counter;
+    impl StorageValue for Counter {
+        type Value = u32;
+    }
+
+    struct Names;
+    impl StorageMap for Names {
+        type Key = u32;
+        type Value = String;
+    }
+
+    #[test]
+    fn values_and_maps() {
+        assert_eq!(Counter::get(), None);
+        Counter::put(1);
+        Counter::mutate(|value| *value = value.map(|v| v + 1));
+        assert_eq!(Counter::get(), Some(2));
+        assert_eq!(Counter::kill(), Some(2));
+        assert!(!Counter::exists());
+
+        Names::insert(2, "B".to_string());
+        Names::insert(1, "A".to_string());
+        assert!(Names::contains_key(&1));
+        assert_eq!(
+            Names::iter(),
+            vec![(1, "A".to_string()), (2, "B".to_string())]
+        );
+        Names::mutate(&1, |name| *name = None);
+        assert_eq!(Names::get(&1), None);
+        assert_eq!(Names::remove(&2), Some("B".to_string()));
+    }
+
+    #[test]
+    fn rollback_discards_changes() {
+        Counter::put(1);
+        let result: Result<(), &str> = transactional(|| {
+            Counter::put(2);
+            Names::insert(1, "A".to_string());
+            Err("failed")
+        });
+        assert_eq!(result, Err("failed"));
+        assert_eq!(Counter::get(), Some(1));
+        assert_eq!(Names::get(&1), None);
+    }
+
+    #[test]
+    fn nested_transactions() {
+        Counter::put(1);
+        let _ = transactional(|| {
+            Counter::put(2);
+            // The inner layer fails, the outer one keeps its own change.
+            let _ = transactional(|| {
+                Counter::put(3);
+                Err::<(), _>("inner failed")
+            });
+            assert_eq!(Counter::get(), Some(2));
+
+            transactional(|| {
+                Names::insert(1, "A".to_string());
+                Ok::<_, &str>(())
+            })?;
+            Err::<(), _>("outer failed")
+        });
+        assert_eq!(Counter::get(), Some(1));
+        assert_eq!(Names::get(&1), None);
+    }
+}
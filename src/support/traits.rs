@@ -0,0 +1,269 @@
+use crate::support::{DispatchResult, Weight};
+
+/// A value provided to a pallet by the runtime through its `Config`, usually a constant.
+pub trait Get<T> {
+    fn get() -> T;
+}
+
+/// A `Get` implementation returning a constant `u32`.
+pub struct ConstU32<const N: u32>;
+impl<const N: u32> Get<u32> for ConstU32<N> {
+    fn get() -> u32 {
+        N
+    }
+}
+
+/// A `Get` implementation returning a constant `u64`.
+pub struct ConstU64<const N: u64>;
+impl<const N: u64> Get<u64> for ConstU64<N> {
+    fn get() -> u64 {
+        N
+    }
+}
+
+/// A `Get` implementation returning a constant `u128`.
+pub struct ConstU128<const N: u128>;
+impl<const N: u128> Get<u128> for ConstU128<N> {
+    fn get() -> u128 {
+        N
+    }
+}
+
+/// A conversion from `A` to `B` provided to a pallet by the runtime, for types the pallet cannot
+/// convert on its own.
+pub trait Convert<A, B> {
+    fn convert(a: A) -> B;
+}
+
+/// A `Convert` implementation for types that already convert into each other with `Into`.
+pub struct ConvertInto;
+impl<A: Into<B>, B> Convert<A, B> for ConvertInto {
+    fn convert(a: A) -> B {
+        a.into()
+    }
+}
+
+/// A check that a caller may dispatch a privileged call, provided to a pallet by the runtime
+/// through its `Config`.
+///
+/// Privileged callers are accounts no one holds the key of, like the root account or the account
+/// a collective dispatches its motions as.
+pub trait EnsureOrigin<AccountId> {
+    fn ensure_origin(caller: &AccountId) -> DispatchResult;
+}
+
+/// A kind of permission that allows some calls of type `Call` and not others.
+pub trait InstanceFilter<Call> {
+    fn filter(&self, call: &Call) -> bool;
+}
+
+/// The logic a pallet runs at fixed points of every block.
+///
+/// `macros::runtime` calls these functions on every pallet, in the order the pallets are declared
+/// in the runtime. Pallets implement only the functions they need, the others keep their default,
+/// empty bodies.
+pub trait Hooks<BlockNumber> {
+    /// Called at the start of the block, before the inherents and the extrinsics are dispatched.
+    /// State set by inherents, like the time of the block, is still that of the previous block.
+    ///
+    /// Returns the weight it consumed.
+    fn on_initialize(_n: BlockNumber) -> Weight {
+        0
+    }
+
+    /// Called once the inherents of the block are dispatched. The block is invalid if it returns
+    /// an error, for instance because an inherent the pallet requires is missing.
+    fn check_inherents(_n: BlockNumber) -> DispatchResult {
+        Ok(())
+    }
+
+    /// Called once all extrinsics are dispatched, with the weight left in the block. The pallet
+    /// can use it for work that can wait, like cleaning up storage.
+    ///
+    /// Returns the weight it consumed, which must not exceed `remaining_weight`.
+    fn on_idle(_n: BlockNumber, _remaining_weight: Weight) -> Weight {
+        0
+    }
+
+    /// Called at the very end of the block.
+    fn on_finalize(_n: BlockNumber) {}
+}
+
+/// Identifier of a lock placed on an account's free balance.
+pub type LockIdentifier = [u8; 8];
+
+/// Identifier of a named reserve (a "hold") on an account's balance.
+pub type ReserveIdentifier = [u8; 8];
+
+/// The kinds of withdrawal a lock applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WithdrawReasons {
+    /// Funds cannot be transferred to another account.
+    Transfer,
+    /// Funds cannot be moved into the reserved balance.
+    Reserve,
+    /// Funds cannot be moved at all.
+    All,
+}
+
+impl WithdrawReasons {
+    /// Whether a lock with these reasons restricts a withdrawal for `reason`.
+    pub fn restricts(&self, reason: WithdrawReasons) -> bool {
+        matches!(
+            (self, reason),
+            (WithdrawReasons::All, _)
+                | (_, WithdrawReasons::All)
+                | (WithdrawReasons::Transfer, WithdrawReasons::Transfer)
+                | (WithdrawReasons::Reserve, WithdrawReasons::Reserve)
+        )
+    }
+}
+
+/// Where reserved funds end up when they are repatriated to another account.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BalanceStatus {
+    Free,
+    Reserved,
+}
+
+/// An amount of funds that entered or left circulation and still has to be accounted for.
+///
+/// Withdrawing from an account yields a negative imbalance, depositing into one a positive
+/// imbalance. Callers can inspect them, merge them, or hand a negative imbalance back to the
+/// currency to credit another account with the withdrawn funds.
+pub trait Imbalance<Balance>: Sized {
+    fn zero() -> Self;
+
+    /// The amount of funds this imbalance represents.
+    fn peek(&self) -> Balance;
+
+    fn merge(self, other: Self) -> Self;
+}
+
+/// A handler for funds withdrawn from circulation, such as fees and slashes, that should be
+/// credited somewhere instead of vanishing.
+pub trait OnUnbalanced<Imbalance> {
+    fn on_unbalanced(amount: Imbalance);
+}
+
+/// Drops the funds, which stay out of circulation.
+impl<Imbalance> OnUnbalanced<Imbalance> for () {
+    fn on_unbalanced(_amount: Imbalance) {}
+}
+
+/// A fungible currency that pallets can move funds with, without knowing which pallet provides
+/// it.
+///
+/// Pallets name it as an associated type of their `Config`, and the runtime links it to the
+/// balances pallet. All functions work directly on the shared storage, so pallets call them as
+/// `T::Currency::transfer(..)` from within their own calls.
+pub trait Currency<AccountId> {
+    type Balance;
+    type PositiveImbalance: Imbalance<Self::Balance>;
+    type NegativeImbalance: Imbalance<Self::Balance>;
+
+    /// The funds `who` can spend, before any locks.
+    fn free_balance(who: &AccountId) -> Self::Balance;
+
+    /// All funds of `who`, free and reserved.
+    fn total_balance(who: &AccountId) -> Self::Balance;
+
+    /// The sum of all balances in circulation.
+    fn total_issuance() -> Self::Balance;
+
+    fn transfer(from: &AccountId, to: &AccountId, value: Self::Balance) -> DispatchResult;
+
+    /// Remove `value` from the free balance of `who` and from circulation, provided no lock with
+    /// `reasons` prevents it.
+    fn withdraw(
+        who: &AccountId,
+        value: Self::Balance,
+        reasons: WithdrawReasons,
+    ) -> Result<Self::NegativeImbalance, &'static str>;
+
+    /// Add `value` to the free balance of `who`, bringing new funds into circulation.
+    fn deposit_creating(who: &AccountId, value: Self::Balance) -> Self::PositiveImbalance;
+
+    /// Credit `who` with funds previously withdrawn from another account.
+    fn resolve_creating(who: &AccountId, imbalance: Self::NegativeImbalance);
+}
+
+/// A currency where funds can be set aside from the free balance of an account.
+///
+/// Reserved funds still belong to the account but cannot be transferred until they are
+/// unreserved. Other pallets use this to escrow deposits, bonds and bids.
+pub trait ReservableCurrency<AccountId>: Currency<AccountId> {
+    fn reserved_balance(who: &AccountId) -> Self::Balance;
+
+    /// Whether `value` could be reserved from `who` right now.
+    fn can_reserve(who: &AccountId, value: Self::Balance) -> bool;
+
+    /// Move `value` from the free balance of `who` into its reserved balance.
+    fn reserve(who: &AccountId, value: Self::Balance) -> DispatchResult;
+
+    /// Move up to `value` from the reserved balance of `who` back into its free balance.
+    ///
+    /// Returns the amount that could not be unreserved.
+    fn unreserve(who: &AccountId, value: Self::Balance) -> Self::Balance;
+
+    /// Remove up to `value` from the reserved balance of `who` and from circulation.
+    ///
+    /// Returns the removed funds, and the amount that could not be removed.
+    fn slash_reserved(
+        who: &AccountId,
+        value: Self::Balance,
+    ) -> (Self::NegativeImbalance, Self::Balance);
+
+    /// Move up to `value` from the reserved balance of `slashed` to the balance of `beneficiary`,
+    /// either into its free or reserved part depending on `status`.
+    ///
+    /// Returns the amount that could not be moved.
+    fn repatriate_reserved(
+        slashed: &AccountId,
+        beneficiary: &AccountId,
+        value: Self::Balance,
+        status: BalanceStatus,
+    ) -> Result<Self::Balance, &'static str>;
+}
+
+/// A currency where reserves can be tagged with an identifier, so that several features can hold
+/// funds of the same account without touching each other's part of the reserve.
+pub trait NamedReservableCurrency<AccountId>: ReservableCurrency<AccountId> {
+    fn reserved_balance_named(id: &ReserveIdentifier, who: &AccountId) -> Self::Balance;
+
+    fn reserve_named(
+        id: &ReserveIdentifier,
+        who: &AccountId,
+        value: Self::Balance,
+    ) -> DispatchResult;
+
+    /// Returns the amount that could not be unreserved.
+    fn unreserve_named(
+        id: &ReserveIdentifier,
+        who: &AccountId,
+        value: Self::Balance,
+    ) -> Self::Balance;
+}
+
+/// A currency where part of the free balance can be locked against some kinds of withdrawal.
+///
+/// Locks do not stack: the amount frozen for a given reason is the largest lock with that reason.
+pub trait LockableCurrency<AccountId>: Currency<AccountId> {
+    /// Create or replace the lock `id` on the balance of `who`.
+    fn set_lock(
+        id: LockIdentifier,
+        who: &AccountId,
+        amount: Self::Balance,
+        reasons: WithdrawReasons,
+    );
+
+    /// Like `set_lock`, but never decreases the amount of an existing lock.
+    fn extend_lock(
+        id: LockIdentifier,
+        who: &AccountId,
+        amount: Self::Balance,
+        reasons: WithdrawReasons,
+    );
+
+    fn remove_lock(id: LockIdentifier, who: &AccountId);
+}
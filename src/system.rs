@@ -1,50 +1,173 @@
-use std::{collections::BTreeMap, ops::AddAssign};
+use std::{fmt::Debug, marker::PhantomData, ops::AddAssign};
 
 use num::traits::{CheckedAdd, CheckedSub, One, Zero};
 
-pub trait Config {
-    type BlockNumber: Clone + Zero + Copy + One + AddAssign + CheckedAdd + CheckedSub;
-    type AccountId: Ord + Clone;
+use crate::support::{
+    codec::Encode,
+    storage::{StorageMap, StorageValue},
+    traits::{EnsureOrigin, Get},
+    DispatchResult, Weight,
+};
+
+pub trait Config: Sized + 'static {
+    type BlockNumber: Clone + Zero + Copy + One + AddAssign + CheckedAdd + CheckedSub + Ord + Debug;
+    type AccountId: Ord + Clone + Debug + Encode;
     type Nonce: Copy + Zero + One;
+    /// The aggregated event type of the runtime, which every pallet's events convert into.
+    type RuntimeEvent: Clone + Debug + PartialEq;
+    /// The largest weight a block can hold.
+    type MaximumBlockWeight: Get<Weight>;
+    /// The weight of dispatching an extrinsic, whatever its call.
+    type ExtrinsicBaseWeight: Get<Weight>;
+    /// The account privileged calls are dispatched as, for instance by governance. No extrinsic
+    /// can be signed by it.
+    type RootAccount: Get<Self::AccountId>;
+}
+
+/// The current block number.
+pub struct Number<T>(PhantomData<T>);
+impl<T: Config> StorageValue for Number<T> {
+    type Value = T::BlockNumber;
+}
+
+/// The number of extrinsics each account has submitted.
+pub struct AccountNonce<T>(PhantomData<T>);
+impl<T: Config> StorageMap for AccountNonce<T> {
+    type Key = T::AccountId;
+    type Value = T::Nonce;
+}
+
+/// The events deposited in the current block.
+pub struct Events<T>(PhantomData<T>);
+impl<T: Config> StorageValue for Events<T> {
+    type Value = Vec<T::RuntimeEvent>;
+}
+
+/// The weight consumed so far in the current block.
+pub struct BlockWeight<T>(PhantomData<T>);
+impl<T: Config> StorageValue for BlockWeight<T> {
+    type Value = Weight;
+}
+
+/// The code of the runtime, as last set by `set_code`.
+pub struct Code<T>(PhantomData<T>);
+impl<T: Config> StorageValue for Code<T> {
+    type Value = Vec<u8>;
+}
+
+/// An `EnsureOrigin` implementation accepting only the root account.
+pub struct EnsureRoot<T>(PhantomData<T>);
+impl<T: Config> EnsureOrigin<T::AccountId> for EnsureRoot<T> {
+    fn ensure_origin(caller: &T::AccountId) -> DispatchResult {
+        Pallet::<T>::ensure_root(caller)
+    }
 }
 
-#[derive(Debug)]
-pub struct Pallet<T: Config> {
-    block_number: T::BlockNumber,
-    nonce: BTreeMap<T::AccountId, T::Nonce>,
+/// The system pallet. Its state lives in the shared storage, so every pallet can read it through
+/// the associated functions below.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pallet<T: Config>(PhantomData<T>);
+
+/// A runtime exposes these calls unless its `system` field is marked `#[no_call]`.
+#[macros::call]
+impl<T: Config> Pallet<T> {
+    /// Do nothing. The remark is only recorded in the block that includes it.
+    #[allow(unused_variables)]
+    pub fn remark(&mut self, _caller: T::AccountId, remark: Vec<u8>) -> DispatchResult {
+        Ok(())
+    }
+
+    /// Replace the code of the runtime. Only root can call it, for instance through a referendum.
+    ///
+    /// This runtime does not run the code it stores. It is kept for the node to upgrade to.
+    pub fn set_code(&mut self, caller: T::AccountId, code: Vec<u8>) -> DispatchResult {
+        Self::ensure_root(&caller)?;
+        Code::<T>::put(code);
+        Ok(())
+    }
 }
 
 impl<T: Config> Pallet<T> {
     pub fn new() -> Self {
-        Pallet {
-            block_number: T::BlockNumber::zero(),
-            nonce: BTreeMap::new(),
+        Pallet(PhantomData)
+    }
+
+    pub fn block_number() -> T::BlockNumber {
+        Number::<T>::get().unwrap_or_else(T::BlockNumber::zero)
+    }
+
+    pub fn inc_block_number() {
+        Number::<T>::put(
+            Self::block_number()
+                .checked_add(&T::BlockNumber::one())
+                .unwrap(),
+        );
+    }
+
+    pub fn inc_nonce(to: &T::AccountId) {
+        let nonce = Self::get_nonce(to);
+        AccountNonce::<T>::insert(to.clone(), nonce + T::Nonce::one());
+    }
+
+    pub fn get_nonce(user: &T::AccountId) -> T::Nonce {
+        AccountNonce::<T>::get(user).unwrap_or_else(T::Nonce::zero)
+    }
+
+    pub fn code() -> Option<Vec<u8>> {
+        Code::<T>::get()
+    }
+
+    /// Record an event for the current block.
+    ///
+    /// Events are kept in storage, so the events of an extrinsic that fails are rolled back
+    /// together with the rest of its changes.
+    pub fn deposit_event(event: impl Into<T::RuntimeEvent>) {
+        let event = event.into();
+        Events::<T>::mutate(|events| events.get_or_insert_with(Vec::new).push(event));
+    }
+
+    pub fn events() -> Vec<T::RuntimeEvent> {
+        Events::<T>::get().unwrap_or_default()
+    }
+
+    pub fn reset_events() {
+        Events::<T>::kill();
+    }
+
+    /// Check that `caller` is the root account.
+    pub fn ensure_root(caller: &T::AccountId) -> DispatchResult {
+        if *caller != T::RootAccount::get() {
+            return Err("Caller is not root");
         }
+        Ok(())
     }
 
-    pub fn block_number(&self) -> T::BlockNumber {
-        self.block_number
+    pub fn block_weight() -> Weight {
+        BlockWeight::<T>::get().unwrap_or(0)
     }
 
-    pub fn inc_block_number(&mut self) {
-        self.block_number = self
-            .block_number
-            .checked_add(&T::BlockNumber::one())
-            .unwrap();
+    /// The weight the current block can still hold.
+    pub fn remaining_block_weight() -> Weight {
+        T::MaximumBlockWeight::get().saturating_sub(Self::block_weight())
     }
 
-    pub fn inc_nonce(&mut self, to: &T::AccountId) {
-        let nonce = *self.nonce.get(&to).unwrap_or(&T::Nonce::zero());
-        self.nonce.insert(to.clone(), nonce + T::Nonce::one());
+    /// Account for `weight` consumed in the current block.
+    pub fn register_extra_weight(weight: Weight) {
+        BlockWeight::<T>::put(Self::block_weight().saturating_add(weight));
     }
 
-    pub fn get_nonce(&self, user: &T::AccountId) -> T::Nonce {
-        *self.nonce.get(user).unwrap_or(&T::Nonce::zero())
+    pub fn reset_block_weight() {
+        BlockWeight::<T>::kill();
     }
 }
 
 #[cfg(test)]
 mod test {
+    use crate::{
+        support::traits::{ConstU64, Get},
+        types,
+    };
+
     use super::*;
 
     struct TestConfig {}
@@ -53,26 +176,66 @@ mod test {
         type AccountId = String;
         type BlockNumber = u32;
         type Nonce = u32;
+        type RuntimeEvent = &'static str;
+        type MaximumBlockWeight = ConstU64<100>;
+        type ExtrinsicBaseWeight = ConstU64<10>;
+        type RootAccount = types::RootAccount;
     }
 
+    type System = Pallet<TestConfig>;
+
     #[test]
     fn init_system() {
-        let system: Pallet<TestConfig> = Pallet::new();
-        assert_eq!(system.block_number(), 0);
+        assert_eq!(System::block_number(), 0);
     }
 
     #[test]
     fn inc_block_number() {
-        let mut system: Pallet<TestConfig> = Pallet::new();
-        system.inc_block_number();
-        assert_eq!(system.block_number(), 1);
+        System::inc_block_number();
+        assert_eq!(System::block_number(), 1);
     }
 
     #[test]
     fn inc_nonce() {
-        let mut system: Pallet<TestConfig> = Pallet::new();
         let user = String::from("A");
-        system.inc_nonce(&user);
-        assert_eq!(system.get_nonce(&user), 1);
+        System::inc_nonce(&user);
+        assert_eq!(System::get_nonce(&user), 1);
+    }
+
+    #[test]
+    fn block_weight() {
+        System::register_extra_weight(30);
+        System::register_extra_weight(50);
+        assert_eq!(System::block_weight(), 80);
+        assert_eq!(System::remaining_block_weight(), 20);
+
+        System::register_extra_weight(50);
+        assert_eq!(System::remaining_block_weight(), 0);
+        System::reset_block_weight();
+        assert_eq!(System::remaining_block_weight(), 100);
+    }
+
+    #[test]
+    fn deposit_events() {
+        System::deposit_event("first");
+        System::deposit_event("second");
+        assert_eq!(System::events(), vec!["first", "second"]);
+
+        System::reset_events();
+        assert!(System::events().is_empty());
+    }
+
+    #[test]
+    fn only_root_sets_code() {
+        let mut system = System::new();
+        system.remark("A".to_string(), b"hello".to_vec()).unwrap();
+        assert_eq!(
+            system.set_code("A".to_string(), b"wasm".to_vec()),
+            Err("Caller is not root")
+        );
+        system
+            .set_code(types::RootAccount::get(), b"wasm".to_vec())
+            .unwrap();
+        assert_eq!(System::code(), Some(b"wasm".to_vec()));
     }
 }
@@ -0,0 +1,151 @@
+use std::{fmt::Debug, marker::PhantomData};
+
+use num::traits::{CheckedAdd, Zero};
+
+use crate::{
+    support::{
+        codec::Encode,
+        storage::StorageValue,
+        traits::{Get, Hooks},
+        DispatchResult,
+    },
+    system,
+};
+
+pub trait Config: system::Config<RuntimeEvent: From<Event<Self>>> {
+    /// A point in time, usually in milliseconds since the Unix epoch.
+    type Moment: Copy + Ord + Zero + CheckedAdd + Debug + Encode;
+    /// The smallest time that can pass between two blocks.
+    type MinimumPeriod: Get<Self::Moment>;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event<T: Config> {
+    TimestampSet { now: T::Moment },
+}
+
+/// The time of the current block.
+pub struct Now<T>(PhantomData<T>);
+impl<T: Config> StorageValue for Now<T> {
+    type Value = T::Moment;
+}
+
+/// Whether the time was set in the current block.
+pub struct DidUpdate<T>(PhantomData<T>);
+impl<T: Config> StorageValue for DidUpdate<T> {
+    type Value = bool;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pallet<T: Config>(PhantomData<T>);
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+    /// Set the time of the current block.
+    ///
+    /// The block author must include it exactly once in every block, and `now` must be at least
+    /// `MinimumPeriod` after the time of the previous block.
+    #[inherent]
+    pub fn set(&mut self, now: T::Moment) -> DispatchResult {
+        if DidUpdate::<T>::exists() {
+            return Err("Timestamp must be set only once in the block");
+        }
+        let earliest = Self::now()
+            .checked_add(&T::MinimumPeriod::get())
+            .ok_or("Timestamp overflow")?;
+        if now < earliest {
+            return Err("Timestamp must increment by at least MinimumPeriod between blocks");
+        }
+
+        Now::<T>::put(now);
+        DidUpdate::<T>::put(true);
+        system::Pallet::<T>::deposit_event(Event::TimestampSet { now });
+        Ok(())
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn new() -> Self {
+        Pallet(PhantomData)
+    }
+
+    /// The time of the current block, or zero before the first block.
+    ///
+    /// The time is set by an inherent, after `on_initialize` runs, so `on_initialize` hooks see
+    /// the time of the previous block.
+    pub fn now() -> T::Moment {
+        Now::<T>::get().unwrap_or_else(T::Moment::zero)
+    }
+}
+
+impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
+    fn check_inherents(_n: T::BlockNumber) -> DispatchResult {
+        if !DidUpdate::<T>::exists() {
+            return Err("Timestamp must be set once in the block");
+        }
+        Ok(())
+    }
+
+    fn on_finalize(_n: T::BlockNumber) {
+        DidUpdate::<T>::kill();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        mock::TestConfig,
+        support::{traits::ConstU64, Dispatch, DispatchInherent},
+    };
+
+    use super::*;
+
+    impl Config for TestConfig {
+        type Moment = u64;
+        type MinimumPeriod = ConstU64<3_000>;
+    }
+
+    type Timestamp = Pallet<TestConfig>;
+
+    #[test]
+    fn timestamp_is_set_once_per_block() {
+        let mut timestamp = Timestamp::new();
+        timestamp.set(6_000).unwrap();
+        assert_eq!(Timestamp::now(), 6_000);
+        assert_eq!(
+            timestamp.set(12_000),
+            Err("Timestamp must be set only once in the block")
+        );
+
+        Timestamp::on_finalize(1);
+        assert_eq!(
+            timestamp.set(8_000),
+            Err("Timestamp must increment by at least MinimumPeriod between blocks")
+        );
+        timestamp.set(9_000).unwrap();
+        assert_eq!(Timestamp::now(), 9_000);
+    }
+
+    #[test]
+    fn timestamp_is_only_set_by_inherent() {
+        let mut timestamp = Timestamp::new();
+        assert_eq!(
+            timestamp.dispatch("A".to_string(), Call::set { now: 6_000 }),
+            Err("Inherents cannot be dispatched by a signed extrinsic")
+        );
+        timestamp
+            .dispatch_inherent(Call::set { now: 6_000 })
+            .unwrap();
+        assert_eq!(Timestamp::now(), 6_000);
+    }
+
+    #[test]
+    fn block_without_timestamp_is_invalid() {
+        assert_eq!(
+            Timestamp::check_inherents(1),
+            Err("Timestamp must be set once in the block")
+        );
+        Timestamp::new().set(6_000).unwrap();
+        assert_eq!(Timestamp::check_inherents(1), Ok(()));
+    }
+}
@@ -0,0 +1,410 @@
+use std::{fmt::Debug, marker::PhantomData};
+
+use num::traits::{CheckedAdd, Saturating, SaturatingMul, Unsigned, Zero};
+
+use crate::{
+    support::{
+        hashing::{sha2_256, H256},
+        storage::{StorageMap, StorageValue},
+        traits::{
+            BalanceStatus, Convert, Currency, EnsureOrigin, Get, Hooks, Imbalance, OnUnbalanced,
+            ReservableCurrency, WithdrawReasons,
+        },
+        DispatchResult, Weight,
+    },
+    system,
+};
+
+pub type ProposalIndex = u32;
+
+pub type BalanceOf<T> =
+    <<T as Config>::Currency as Currency<<T as system::Config>::AccountId>>::Balance;
+pub type NegativeImbalanceOf<T> =
+    <<T as Config>::Currency as Currency<<T as system::Config>::AccountId>>::NegativeImbalance;
+
+pub trait Config: system::Config<RuntimeEvent: From<Event<Self>>> {
+    /// The currency the treasury holds and bonds are reserved in.
+    type Currency: ReservableCurrency<
+        Self::AccountId,
+        Balance: Unsigned + Saturating + SaturatingMul + From<u32> + Copy + Ord + Debug,
+    >;
+    /// Turns the hash identifying the treasury into the account id of its pot.
+    type AccountIdFromHash: Convert<H256, Self::AccountId>;
+    /// Who can approve spend proposals.
+    type ApproveOrigin: EnsureOrigin<Self::AccountId>;
+    /// Who can reject spend proposals, slashing their bond.
+    type RejectOrigin: EnsureOrigin<Self::AccountId>;
+    /// The bond of a proposal, as a percentage of the value it asks for.
+    type ProposalBond: Get<u32>;
+    /// The smallest bond of a proposal.
+    type ProposalBondMinimum: Get<BalanceOf<Self>>;
+    /// How often, in blocks, approved proposals are paid out.
+    type SpendPeriod: Get<Self::BlockNumber>;
+    /// The percentage of the funds left after a payout that is burnt.
+    type Burn: Get<u32>;
+    /// The largest number of approved proposals waiting to be paid out.
+    type MaxApprovals: Get<u32>;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proposal<AccountId, Balance> {
+    pub proposer: AccountId,
+    pub value: Balance,
+    pub beneficiary: AccountId,
+    /// The bond reserved from the proposer.
+    pub bond: Balance,
+}
+
+pub type ProposalOf<T> = Proposal<<T as system::Config>::AccountId, BalanceOf<T>>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event<T: Config> {
+    Proposed {
+        proposal_index: ProposalIndex,
+    },
+    /// A spend period started with `budget_remaining` in the pot.
+    Spending {
+        budget_remaining: BalanceOf<T>,
+    },
+    Awarded {
+        proposal_index: ProposalIndex,
+        award: BalanceOf<T>,
+        account: T::AccountId,
+    },
+    /// A proposal was rejected, and its bond moved to the pot.
+    Rejected {
+        proposal_index: ProposalIndex,
+        slashed: BalanceOf<T>,
+    },
+    Burnt {
+        burnt_funds: BalanceOf<T>,
+    },
+    /// The funds left in the pot after a spend period.
+    Rollover {
+        rollover_balance: BalanceOf<T>,
+    },
+    /// Funds withdrawn elsewhere, such as fees or slashes, were credited to the pot.
+    Deposit {
+        value: BalanceOf<T>,
+    },
+}
+
+/// The index of the next proposal.
+pub struct ProposalCount<T>(PhantomData<T>);
+impl<T: Config> StorageValue for ProposalCount<T> {
+    type Value = ProposalIndex;
+}
+
+/// The proposals that were neither paid out nor rejected.
+pub struct Proposals<T>(PhantomData<T>);
+impl<T: Config> StorageMap for Proposals<T> {
+    type Key = ProposalIndex;
+    type Value = ProposalOf<T>;
+}
+
+/// The approved proposals, in the order they are paid out.
+pub struct Approvals<T>(PhantomData<T>);
+impl<T: Config> StorageValue for Approvals<T> {
+    type Value = Vec<ProposalIndex>;
+}
+
+/// The block the last spend period started in.
+pub struct LastSpend<T>(PhantomData<T>);
+impl<T: Config> StorageValue for LastSpend<T> {
+    type Value = T::BlockNumber;
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pallet<T: Config>(PhantomData<T>);
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+    /// Propose to pay `value` from the pot to `beneficiary`, reserving a bond from the caller.
+    ///
+    /// The bond is returned when the proposal is paid out, and moved to the pot if it is
+    /// rejected.
+    pub fn propose_spend(
+        &mut self,
+        caller: T::AccountId,
+        value: BalanceOf<T>,
+        beneficiary: T::AccountId,
+    ) -> DispatchResult {
+        let bond = Self::calculate_bond(value);
+        T::Currency::reserve(&caller, bond)?;
+
+        let proposal_index = ProposalCount::<T>::get().unwrap_or(0);
+        ProposalCount::<T>::put(
+            proposal_index
+                .checked_add(1)
+                .ok_or("Proposal indices are exhausted")?,
+        );
+        Proposals::<T>::insert(
+            proposal_index,
+            Proposal {
+                proposer: caller,
+                value,
+                beneficiary,
+                bond,
+            },
+        );
+        system::Pallet::<T>::deposit_event(Event::Proposed { proposal_index });
+        Ok(())
+    }
+
+    /// Reject a proposal and move its bond to the pot. Only `RejectOrigin` can call it.
+    pub fn reject_proposal(
+        &mut self,
+        caller: T::AccountId,
+        proposal_index: ProposalIndex,
+    ) -> DispatchResult {
+        T::RejectOrigin::ensure_origin(&caller)?;
+        let proposal = Proposals::<T>::remove(&proposal_index).ok_or("Proposal not found")?;
+        Approvals::<T>::mutate(|approvals| {
+            if let Some(approvals) = approvals {
+                approvals.retain(|index| *index != proposal_index);
+            }
+        });
+
+        let unmoved = T::Currency::repatriate_reserved(
+            &proposal.proposer,
+            &Self::account_id(),
+            proposal.bond,
+            BalanceStatus::Free,
+        )?;
+        system::Pallet::<T>::deposit_event(Event::Rejected {
+            proposal_index,
+            slashed: proposal.bond.saturating_sub(unmoved),
+        });
+        Ok(())
+    }
+
+    /// Approve a proposal, to be paid out at the next spend period with enough funds. Only
+    /// `ApproveOrigin` can call it.
+    pub fn approve_proposal(
+        &mut self,
+        caller: T::AccountId,
+        proposal_index: ProposalIndex,
+    ) -> DispatchResult {
+        T::ApproveOrigin::ensure_origin(&caller)?;
+        if !Proposals::<T>::contains_key(&proposal_index) {
+            return Err("Proposal not found");
+        }
+        let mut approvals = Self::approvals();
+        if approvals.contains(&proposal_index) {
+            return Err("Proposal already approved");
+        }
+        if approvals.len() >= T::MaxApprovals::get() as usize {
+            return Err("Too many approvals");
+        }
+        approvals.push(proposal_index);
+        Approvals::<T>::put(approvals);
+        Ok(())
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn new() -> Self {
+        Pallet(PhantomData)
+    }
+
+    /// The account of the pot, derived from the hash of `b"treasury"`. No one holds its key.
+    pub fn account_id() -> T::AccountId {
+        T::AccountIdFromHash::convert(sha2_256(b"treasury"))
+    }
+
+    /// The funds the pot can spend.
+    pub fn pot() -> BalanceOf<T> {
+        T::Currency::free_balance(&Self::account_id())
+    }
+
+    pub fn proposal(proposal_index: ProposalIndex) -> Option<ProposalOf<T>> {
+        Proposals::<T>::get(&proposal_index)
+    }
+
+    pub fn approvals() -> Vec<ProposalIndex> {
+        Approvals::<T>::get().unwrap_or_default()
+    }
+
+    fn calculate_bond(value: BalanceOf<T>) -> BalanceOf<T> {
+        let bond = value.saturating_mul(&T::ProposalBond::get().into()) / 100.into();
+        bond.max(T::ProposalBondMinimum::get())
+    }
+
+    /// Pay out the approved proposals the pot can afford, in order, then burn part of what is
+    /// left. The proposals that cannot be afforded wait for the next spend period.
+    ///
+    /// Returns the number of proposals paid out.
+    fn spend_funds() -> u32 {
+        let pot = Self::account_id();
+        let mut budget_remaining = Self::pot();
+        system::Pallet::<T>::deposit_event(Event::Spending { budget_remaining });
+
+        let mut paid = 0;
+        let mut approvals = Self::approvals();
+        approvals.retain(|proposal_index| {
+            let Some(proposal) = Proposals::<T>::get(proposal_index) else {
+                return false;
+            };
+            if proposal.value > budget_remaining
+                || T::Currency::transfer(&pot, &proposal.beneficiary, proposal.value).is_err()
+            {
+                return true;
+            }
+            budget_remaining = budget_remaining.saturating_sub(proposal.value);
+            Proposals::<T>::remove(proposal_index);
+            T::Currency::unreserve(&proposal.proposer, proposal.bond);
+            paid += 1;
+            system::Pallet::<T>::deposit_event(Event::Awarded {
+                proposal_index: *proposal_index,
+                award: proposal.value,
+                account: proposal.beneficiary,
+            });
+            false
+        });
+        Approvals::<T>::put(approvals);
+
+        let burn = budget_remaining.saturating_mul(&T::Burn::get().min(100).into()) / 100.into();
+        if !burn.is_zero() {
+            if let Ok(burnt) = T::Currency::withdraw(&pot, burn, WithdrawReasons::Transfer) {
+                budget_remaining = budget_remaining.saturating_sub(burnt.peek());
+                system::Pallet::<T>::deposit_event(Event::Burnt {
+                    burnt_funds: burnt.peek(),
+                });
+            }
+        }
+        system::Pallet::<T>::deposit_event(Event::Rollover {
+            rollover_balance: budget_remaining,
+        });
+        paid
+    }
+}
+
+/// Credits the funds to the pot.
+impl<T: Config> OnUnbalanced<NegativeImbalanceOf<T>> for Pallet<T> {
+    fn on_unbalanced(amount: NegativeImbalanceOf<T>) {
+        let value = amount.peek();
+        T::Currency::resolve_creating(&Self::account_id(), amount);
+        system::Pallet::<T>::deposit_event(Event::Deposit { value });
+    }
+}
+
+impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {
+    /// Start a spend period every `SpendPeriod` blocks.
+    ///
+    /// The spend period weighs as much as an extrinsic, plus one per proposal paid out.
+    fn on_initialize(n: T::BlockNumber) -> Weight {
+        let last_spend = LastSpend::<T>::get().unwrap_or_else(T::BlockNumber::zero);
+        let next_spend = last_spend.checked_add(&T::SpendPeriod::get());
+        if next_spend.is_some_and(|next_spend| n >= next_spend) {
+            LastSpend::<T>::put(n);
+            let paid = Self::spend_funds();
+            return <T as system::Config>::ExtrinsicBaseWeight::get() * (1 + paid as Weight);
+        }
+        0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        balances,
+        mock::{TestConfig, TestEvent},
+        support::traits::{ConstU128, ConstU32},
+        types::{self, HexAccountId},
+    };
+
+    use super::*;
+
+    impl Config for TestConfig {
+        type Currency = balances::Pallet<TestConfig>;
+        type AccountIdFromHash = HexAccountId;
+        type ApproveOrigin = system::EnsureRoot<TestConfig>;
+        type RejectOrigin = system::EnsureRoot<TestConfig>;
+        type ProposalBond = ConstU32<5>;
+        type ProposalBondMinimum = ConstU128<2>;
+        type SpendPeriod = ConstU32<2>;
+        type Burn = ConstU32<10>;
+        type MaxApprovals = ConstU32<2>;
+    }
+
+    type System = system::Pallet<TestConfig>;
+    type Balances = balances::Pallet<TestConfig>;
+    type Treasury = Pallet<TestConfig>;
+
+    fn run_to_block(n: types::BlockNumber) {
+        while System::block_number() < n {
+            System::inc_block_number();
+            Treasury::on_initialize(System::block_number());
+        }
+    }
+
+    #[test]
+    fn approved_proposals_are_paid_out_each_spend_period() {
+        let (a, b, root) = ("A".to_string(), "B".to_string(), types::RootAccount::get());
+        Balances::set_balance(&a, 100);
+        Balances::set_balance(&Treasury::account_id(), 100);
+        let mut treasury = Treasury::new();
+
+        treasury.propose_spend(a.clone(), 60, b.clone()).unwrap();
+        treasury.propose_spend(a.clone(), 20, b.clone()).unwrap();
+        treasury.propose_spend(a.clone(), 50, b.clone()).unwrap();
+        assert_eq!(Balances::reserved_balance(&a), 3 + 2 + 2);
+        assert_eq!(
+            treasury.approve_proposal(a.clone(), 0),
+            Err("Caller is not root")
+        );
+        treasury.approve_proposal(root.clone(), 0).unwrap();
+        treasury.approve_proposal(root.clone(), 2).unwrap();
+        assert_eq!(
+            treasury.approve_proposal(root.clone(), 1),
+            Err("Too many approvals")
+        );
+
+        // The pot affords the first proposal only, and 10% of the rest is burnt.
+        run_to_block(2);
+        assert_eq!(Balances::balance(&b), 60);
+        assert_eq!(Treasury::pot(), 36);
+        assert_eq!(Treasury::approvals(), vec![2]);
+        assert_eq!(Treasury::proposal(0), None);
+        assert_eq!(Balances::reserved_balance(&a), 2 + 2);
+        assert_eq!(Balances::total_issuance(), 200 - 4);
+        assert!(System::events().contains(&TestEvent::Treasury(Event::Burnt { burnt_funds: 4 })));
+
+        // Fees and slashes credited to the pot pay the second proposal out.
+        let fee = Balances::withdraw(&a, 20, WithdrawReasons::Transfer).unwrap();
+        Treasury::on_unbalanced(fee);
+        run_to_block(3);
+        assert_eq!(Balances::balance(&b), 60);
+        run_to_block(4);
+        assert_eq!(Balances::balance(&b), 110);
+        assert!(Treasury::approvals().is_empty());
+    }
+
+    #[test]
+    fn rejected_proposal_bond_goes_to_the_pot() {
+        let (a, root) = ("A".to_string(), types::RootAccount::get());
+        Balances::set_balance(&a, 100);
+        let mut treasury = Treasury::new();
+
+        treasury.propose_spend(a.clone(), 100, a.clone()).unwrap();
+        assert_eq!(Balances::reserved_balance(&a), 5);
+        assert_eq!(
+            treasury.reject_proposal(a.clone(), 0),
+            Err("Caller is not root")
+        );
+        treasury.reject_proposal(root.clone(), 0).unwrap();
+        assert_eq!(treasury.reject_proposal(root, 0), Err("Proposal not found"));
+
+        assert_eq!(Balances::reserved_balance(&a), 0);
+        assert_eq!(Balances::balance(&a), 95);
+        assert_eq!(Treasury::pot(), 5);
+    }
+
+    #[test]
+    fn pot_account_is_stable() {
+        assert_eq!(
+            Treasury::account_id(),
+            "66fa9f9d3186b9c13a66b004fc8bb5f3359c4deb17197266895fb8d3c67c3b17"
+        );
+    }
+}
@@ -1,11 +1,67 @@
-use crate::{support, RuntimeCall};
+use crate::{
+    support::{
+        self,
+        codec::Encode,
+        hashing::{to_hex, H256},
+        traits::{Convert, Get, InstanceFilter},
+    },
+    RuntimeCall,
+};
 
 pub type AccountId = String;
 pub type Balance = u128;
 pub type BlockNumber = u32;
 pub type Nonce = u32;
-pub type Content = &'static str;
+pub type Content = Vec<u8>;
+/// Milliseconds since the Unix epoch.
+pub type Moment = u64;
+pub type AssetId = u32;
+pub type CollectionId = u32;
+pub type ItemId = u32;
+
+/// The root account is named `root`. The runtime rejects extrinsics signed by it.
+pub struct RootAccount;
+impl Get<AccountId> for RootAccount {
+    fn get() -> AccountId {
+        String::from("root")
+    }
+}
 
 pub type Extrinsic = support::Extrinsic<AccountId, RuntimeCall>;
 pub type Header = support::Header<BlockNumber>;
-pub type Block = support::Block<Header, Extrinsic>;
+pub type Block = support::Block<Header, RuntimeCall, Extrinsic>;
+
+/// Derives the id of a keyless account, like a multisig account, from a hash.
+pub struct HexAccountId;
+impl Convert<H256, AccountId> for HexAccountId {
+    fn convert(hash: H256) -> AccountId {
+        to_hex(&hash)
+    }
+}
+
+/// The kinds of proxy an account can give to a delegate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProxyType {
+    /// Allows every call.
+    Any,
+    /// Allows the calls of the balances pallet.
+    BalancesOnly,
+    /// Allows the calls of the proof of existence pallet.
+    ClaimsOnly,
+}
+
+impl Encode for ProxyType {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        dest.push(self.clone() as u8);
+    }
+}
+
+impl InstanceFilter<RuntimeCall> for ProxyType {
+    fn filter(&self, call: &RuntimeCall) -> bool {
+        match self {
+            ProxyType::Any => true,
+            ProxyType::BalancesOnly => matches!(call, RuntimeCall::balances(_)),
+            ProxyType::ClaimsOnly => matches!(call, RuntimeCall::proof_of_existence(_)),
+        }
+    }
+}
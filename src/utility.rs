@@ -0,0 +1,306 @@
+use std::marker::PhantomData;
+
+use crate::{
+    support::{storage::transactional, traits::Hooks, DispatchResult, Dispatchable},
+    system,
+};
+
+pub trait Config: system::Config<RuntimeEvent: From<Event<Self>>> {
+    /// The calls a batch is made of, usually the `RuntimeCall` of the runtime.
+    type RuntimeCall: Dispatchable<Caller = Self::AccountId>;
+}
+
+/// Failing calls are identified by their index in the batch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event<T: Config> {
+    /// A call of a `batch` or `batch_all` failed, and the calls after it were not dispatched.
+    BatchInterrupted {
+        who: T::AccountId,
+        index: u32,
+        error: &'static str,
+    },
+    /// Every call of the batch was dispatched successfully.
+    BatchCompleted { who: T::AccountId },
+    /// Every call of a `force_batch` was dispatched, but some of them failed.
+    BatchCompletedWithErrors { who: T::AccountId },
+    /// A call of a `force_batch` failed.
+    ItemFailed {
+        who: T::AccountId,
+        index: u32,
+        error: &'static str,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pallet<T: Config>(PhantomData<T>);
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+    /// Dispatch `calls` in order until one of them fails.
+    ///
+    /// The calls before the failing one keep their changes.
+    pub fn batch(&mut self, caller: T::AccountId, calls: Vec<T::RuntimeCall>) -> DispatchResult {
+        if let Err((index, error)) = Self::dispatch_all(&caller, calls) {
+            system::Pallet::<T>::deposit_event(Event::BatchInterrupted {
+                who: caller,
+                index,
+                error,
+            });
+            return Ok(());
+        }
+        system::Pallet::<T>::deposit_event(Event::BatchCompleted { who: caller });
+        Ok(())
+    }
+
+    /// Dispatch `calls` in order, keeping their changes only if all of them succeed.
+    ///
+    /// A failure is returned as the error of the failing call, so that whatever dispatched the
+    /// batch, like another `batch_all`, is rolled back too. The index of the failing call is
+    /// reported with `Event::BatchInterrupted`.
+    pub fn batch_all(
+        &mut self,
+        caller: T::AccountId,
+        calls: Vec<T::RuntimeCall>,
+    ) -> DispatchResult {
+        if let Err((index, error)) = transactional(|| Self::dispatch_all(&caller, calls)) {
+            system::Pallet::<T>::deposit_event(Event::BatchInterrupted {
+                who: caller,
+                index,
+                error,
+            });
+            return Err(error);
+        }
+        system::Pallet::<T>::deposit_event(Event::BatchCompleted { who: caller });
+        Ok(())
+    }
+
+    /// Dispatch every call of `calls`, whether or not the previous ones failed.
+    pub fn force_batch(
+        &mut self,
+        caller: T::AccountId,
+        calls: Vec<T::RuntimeCall>,
+    ) -> DispatchResult {
+        let mut failed = false;
+        for (index, call) in calls.into_iter().enumerate() {
+            if let Err(error) = transactional(|| call.dispatch(caller.clone())) {
+                failed = true;
+                system::Pallet::<T>::deposit_event(Event::ItemFailed {
+                    who: caller.clone(),
+                    index: index as u32,
+                    error,
+                });
+            }
+        }
+        let event = if failed {
+            Event::BatchCompletedWithErrors { who: caller }
+        } else {
+            Event::BatchCompleted { who: caller }
+        };
+        system::Pallet::<T>::deposit_event(event);
+        Ok(())
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn new() -> Self {
+        Pallet(PhantomData)
+    }
+
+    /// Dispatch `calls` in order, each in its own transactional layer, and stop at the first one
+    /// that fails.
+    fn dispatch_all(
+        caller: &T::AccountId,
+        calls: Vec<T::RuntimeCall>,
+    ) -> Result<(), (u32, &'static str)> {
+        for (index, call) in calls.into_iter().enumerate() {
+            transactional(|| call.dispatch(caller.clone())).map_err(|e| (index as u32, e))?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        balances,
+        support::{traits::ConstU64, Dispatch},
+        types,
+    };
+
+    use super::*;
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    struct TestConfig;
+
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    enum TestEvent {
+        Balances(balances::Event<TestConfig>),
+        Utility(Event<TestConfig>),
+    }
+
+    impl From<balances::Event<TestConfig>> for TestEvent {
+        fn from(event: balances::Event<TestConfig>) -> Self {
+            TestEvent::Balances(event)
+        }
+    }
+
+    impl From<Event<TestConfig>> for TestEvent {
+        fn from(event: Event<TestConfig>) -> Self {
+            TestEvent::Utility(event)
+        }
+    }
+
+    enum TestCall {
+        Balances(balances::Call<TestConfig>),
+        Utility(Call<TestConfig>),
+    }
+
+    impl Dispatchable for TestCall {
+        type Caller = types::AccountId;
+
+        fn dispatch(self, caller: Self::Caller) -> DispatchResult {
+            match self {
+                TestCall::Balances(call) => balances::Pallet::new().dispatch(caller, call),
+                TestCall::Utility(call) => Pallet::new().dispatch(caller, call),
+            }
+        }
+    }
+
+    impl system::Config for TestConfig {
+        type AccountId = types::AccountId;
+        type BlockNumber = types::BlockNumber;
+        type Nonce = types::Nonce;
+        type RuntimeEvent = TestEvent;
+        type MaximumBlockWeight = ConstU64<1_000>;
+        type ExtrinsicBaseWeight = ConstU64<10>;
+        type RootAccount = types::RootAccount;
+    }
+
+    impl balances::Config for TestConfig {
+        type Balance = types::Balance;
+    }
+
+    impl Config for TestConfig {
+        type RuntimeCall = TestCall;
+    }
+
+    type Balances = balances::Pallet<TestConfig>;
+
+    fn transfer(to: &str, amount: u128) -> TestCall {
+        TestCall::Balances(balances::Call::transfer {
+            to: to.to_string(),
+            amount,
+        })
+    }
+
+    fn utility_events() -> Vec<Event<TestConfig>> {
+        system::Pallet::<TestConfig>::events()
+            .into_iter()
+            .filter_map(|event| match event {
+                TestEvent::Utility(event) => Some(event),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn batch_stops_at_first_error() {
+        let a = "A".to_string();
+        Balances::set_balance(&a, 100);
+        let mut utility: Pallet<TestConfig> = Pallet::new();
+
+        let calls = vec![transfer("B", 60), transfer("C", 60), transfer("D", 10)];
+        utility.batch(a.clone(), calls).unwrap();
+        assert_eq!(Balances::balance(&"B".to_string()), 60);
+        assert_eq!(Balances::balance(&"D".to_string()), 0);
+        assert_eq!(
+            utility_events(),
+            vec![Event::BatchInterrupted {
+                who: a,
+                index: 1,
+                error: "Insufficient fund"
+            }]
+        );
+    }
+
+    #[test]
+    fn batch_all_rolls_back_every_call() {
+        let a = "A".to_string();
+        Balances::set_balance(&a, 100);
+        let mut utility: Pallet<TestConfig> = Pallet::new();
+
+        let calls = vec![transfer("B", 60), transfer("C", 60)];
+        assert_eq!(
+            utility.batch_all(a.clone(), calls),
+            Err("Insufficient fund")
+        );
+        assert_eq!(Balances::balance(&a), 100);
+        assert_eq!(Balances::balance(&"B".to_string()), 0);
+        assert_eq!(
+            utility_events(),
+            vec![Event::BatchInterrupted {
+                who: a.clone(),
+                index: 1,
+                error: "Insufficient fund"
+            }]
+        );
+
+        system::Pallet::<TestConfig>::reset_events();
+        let calls = vec![transfer("B", 60), transfer("C", 40)];
+        utility.batch_all(a.clone(), calls).unwrap();
+        assert_eq!(Balances::balance(&a), 0);
+        assert_eq!(utility_events(), vec![Event::BatchCompleted { who: a }]);
+    }
+
+    #[test]
+    fn nested_batch_all_failure_rolls_back_outer_batch() {
+        let a = "A".to_string();
+        Balances::set_balance(&a, 100);
+        let mut utility: Pallet<TestConfig> = Pallet::new();
+
+        let inner = TestCall::Utility(Call::batch_all {
+            calls: vec![transfer("C", 500)],
+        });
+        assert_eq!(
+            utility.batch_all(a.clone(), vec![transfer("B", 50), inner]),
+            Err("Insufficient fund")
+        );
+        assert_eq!(Balances::balance(&a), 100);
+        assert_eq!(Balances::balance(&"B".to_string()), 0);
+        // The event of the inner batch is rolled back with the call that dispatched it.
+        assert_eq!(
+            utility_events(),
+            vec![Event::BatchInterrupted {
+                who: a,
+                index: 1,
+                error: "Insufficient fund"
+            }]
+        );
+    }
+
+    #[test]
+    fn force_batch_continues_past_errors() {
+        let a = "A".to_string();
+        Balances::set_balance(&a, 100);
+        let mut utility: Pallet<TestConfig> = Pallet::new();
+
+        let calls = vec![transfer("B", 60), transfer("C", 60), transfer("D", 40)];
+        utility.force_batch(a.clone(), calls).unwrap();
+        assert_eq!(Balances::balance(&"B".to_string()), 60);
+        assert_eq!(Balances::balance(&"C".to_string()), 0);
+        assert_eq!(Balances::balance(&"D".to_string()), 40);
+        assert_eq!(
+            utility_events(),
+            vec![
+                Event::ItemFailed {
+                    who: a.clone(),
+                    index: 1,
+                    error: "Insufficient fund"
+                },
+                Event::BatchCompletedWithErrors { who: a },
+            ]
+        );
+    }
+}
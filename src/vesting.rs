@@ -0,0 +1,440 @@
+use std::{fmt::Debug, marker::PhantomData};
+
+use num::traits::{CheckedSub, One, Saturating, SaturatingMul, Unsigned, Zero};
+
+use crate::{
+    support::{
+        codec::Encode,
+        storage::StorageMap,
+        traits::{
+            Convert, Currency, Get, Hooks, LockIdentifier, LockableCurrency, WithdrawReasons,
+        },
+        DispatchResult,
+    },
+    system,
+};
+
+/// The name of the lock placed on the balance of vesting accounts.
+pub const VESTING_ID: LockIdentifier = *b"vesting ";
+
+pub type BalanceOf<T> =
+    <<T as Config>::Currency as Currency<<T as system::Config>::AccountId>>::Balance;
+
+pub trait Config: system::Config<RuntimeEvent: From<Event<Self>>> {
+    /// The currency vested funds are locked in, usually provided by the balances pallet.
+    type Currency: LockableCurrency<
+        Self::AccountId,
+        Balance: Unsigned + Saturating + SaturatingMul + Copy + Ord + Debug,
+    >;
+    /// Converts a number of blocks into a balance, to compute how much has vested over them.
+    type BlockNumberToBalance: Convert<Self::BlockNumber, BalanceOf<Self>>;
+    /// The smallest amount that can be transferred with `vested_transfer`.
+    type MinVestedTransfer: Get<BalanceOf<Self>>;
+    /// The largest number of schedules an account can have at once.
+    type MaxVestingSchedules: Get<u32>;
+}
+
+/// Funds unlocking linearly, `per_block` in every block after `starting_block`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VestingInfo<Balance, BlockNumber> {
+    /// The amount locked when the schedule starts.
+    pub locked: Balance,
+    pub per_block: Balance,
+    pub starting_block: BlockNumber,
+}
+
+impl<Balance: Encode, BlockNumber: Encode> Encode for VestingInfo<Balance, BlockNumber> {
+    fn encode_to(&self, dest: &mut Vec<u8>) {
+        self.locked.encode_to(dest);
+        self.per_block.encode_to(dest);
+        self.starting_block.encode_to(dest);
+    }
+}
+
+pub type VestingInfoOf<T> = VestingInfo<BalanceOf<T>, <T as system::Config>::BlockNumber>;
+
+impl<Balance, BlockNumber> VestingInfo<Balance, BlockNumber>
+where
+    Balance: Unsigned + Saturating + SaturatingMul + Copy,
+    BlockNumber: CheckedSub + Copy,
+{
+    /// Whether the schedule locks and unlocks anything at all.
+    pub fn is_valid(&self) -> bool {
+        !self.locked.is_zero() && !self.per_block.is_zero()
+    }
+
+    /// The amount still locked at block `n`.
+    pub fn locked_at<BlockNumberToBalance: Convert<BlockNumber, Balance>>(
+        &self,
+        n: BlockNumber,
+    ) -> Balance {
+        let Some(elapsed) = n.checked_sub(&self.starting_block) else {
+            return self.locked;
+        };
+        let vested = self
+            .per_block
+            .saturating_mul(&BlockNumberToBalance::convert(elapsed));
+        self.locked.saturating_sub(vested)
+    }
+
+    /// The first block in which everything is unlocked, converted to a balance so that it can be
+    /// computed without converting balances back to block numbers.
+    fn ending_block_as_balance<BlockNumberToBalance: Convert<BlockNumber, Balance>>(
+        &self,
+    ) -> Balance {
+        let mut duration = self.locked / self.per_block;
+        if !(self.locked % self.per_block).is_zero() {
+            duration = duration.saturating_add(Balance::one());
+        }
+        BlockNumberToBalance::convert(self.starting_block).saturating_add(duration)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event<T: Config> {
+    /// The amount still locked in the account changed.
+    VestingUpdated {
+        account: T::AccountId,
+        unvested: BalanceOf<T>,
+    },
+    /// Every schedule of the account has vested, and its lock was removed.
+    VestingCompleted { account: T::AccountId },
+}
+
+/// The vesting schedules of each account.
+pub struct Vesting<T>(PhantomData<T>);
+impl<T: Config> StorageMap for Vesting<T> {
+    type Key = T::AccountId;
+    type Value = Vec<VestingInfoOf<T>>;
+}
+
+/// A genesis vesting entry `(who, begin, length, liquid)`.
+#[allow(dead_code)]
+pub type GenesisVesting<T> = (
+    <T as system::Config>::AccountId,
+    <T as system::Config>::BlockNumber,
+    <T as system::Config>::BlockNumber,
+    BalanceOf<T>,
+);
+
+/// Vesting entries to set up in the first block of the chain.
+#[allow(dead_code)]
+pub struct GenesisConfig<T: Config> {
+    /// Each entry locks all of the free balance of `who` except
+    /// `liquid`, and unlocks it linearly over `length` blocks from block `begin` on.
+    pub vesting: Vec<GenesisVesting<T>>,
+}
+
+impl<T: Config> GenesisConfig<T> {
+    /// Lock the funds of every entry. Balances must be set up before.
+    #[allow(dead_code)]
+    pub fn build(&self) -> DispatchResult {
+        for (who, begin, length, liquid) in &self.vesting {
+            let locked = T::Currency::free_balance(who).saturating_sub(*liquid);
+            let length = T::BlockNumberToBalance::convert(*length);
+            if length.is_zero() {
+                return Err("Invalid vesting schedule");
+            }
+            let schedule = VestingInfo {
+                locked,
+                per_block: (locked / length).max(BalanceOf::<T>::one()),
+                starting_block: *begin,
+            };
+            Pallet::<T>::add_vesting_schedule(who, schedule)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pallet<T: Config>(PhantomData<T>);
+
+#[macros::call]
+impl<T: Config> Pallet<T> {
+    /// Unlock the funds of the caller that have vested so far.
+    pub fn vest(&mut self, caller: T::AccountId) -> DispatchResult {
+        Self::do_vest(&caller)
+    }
+
+    /// Unlock the funds of `target` that have vested so far.
+    pub fn vest_other(&mut self, _caller: T::AccountId, target: T::AccountId) -> DispatchResult {
+        Self::do_vest(&target)
+    }
+
+    /// Transfer `schedule.locked` to `target`, locked there under `schedule`.
+    pub fn vested_transfer(
+        &mut self,
+        caller: T::AccountId,
+        target: T::AccountId,
+        schedule: VestingInfoOf<T>,
+    ) -> DispatchResult {
+        if schedule.locked < T::MinVestedTransfer::get() {
+            return Err("Amount transferred is below the minimum vested transfer");
+        }
+        Self::ensure_can_add_schedule(&target, &schedule)?;
+
+        T::Currency::transfer(&caller, &target, schedule.locked)?;
+        Self::add_vesting_schedule(&target, schedule)
+    }
+
+    /// Replace the schedules at `index1` and `index2` of the caller with a single one, which
+    /// unlocks the funds they still lock by the later of their ending blocks.
+    pub fn merge_schedules(
+        &mut self,
+        caller: T::AccountId,
+        index1: u32,
+        index2: u32,
+    ) -> DispatchResult {
+        if index1 == index2 {
+            return Ok(());
+        }
+        let mut schedules = Self::vesting(&caller);
+        let (first, second) = (index1.min(index2) as usize, index1.max(index2) as usize);
+        if second >= schedules.len() {
+            return Err("Schedule index out of bounds");
+        }
+
+        let now = system::Pallet::<T>::block_number();
+        let (a, b) = (schedules.remove(second), schedules.remove(first));
+        let locked = a
+            .locked_at::<T::BlockNumberToBalance>(now)
+            .saturating_add(b.locked_at::<T::BlockNumberToBalance>(now));
+        let ending = a
+            .ending_block_as_balance::<T::BlockNumberToBalance>()
+            .max(b.ending_block_as_balance::<T::BlockNumberToBalance>());
+        let starting_block = now.max(a.starting_block).max(b.starting_block);
+        let duration = ending.saturating_sub(T::BlockNumberToBalance::convert(starting_block));
+        let per_block = if duration.is_zero() {
+            locked
+        } else {
+            (locked / duration).max(BalanceOf::<T>::one())
+        };
+        let merged = VestingInfo {
+            locked,
+            per_block,
+            starting_block,
+        };
+        // Both schedules may have fully vested already, in which case nothing is left to merge.
+        if merged.is_valid() {
+            schedules.push(merged);
+        }
+
+        Vesting::<T>::insert(caller.clone(), schedules);
+        Self::do_vest(&caller)
+    }
+}
+
+impl<T: Config> Pallet<T> {
+    pub fn new() -> Self {
+        Pallet(PhantomData)
+    }
+
+    pub fn vesting(who: &T::AccountId) -> Vec<VestingInfoOf<T>> {
+        Vesting::<T>::get(who).unwrap_or_default()
+    }
+
+    /// The amount of the balance of `who` still locked by vesting, or `None` if it is not vesting.
+    pub fn vesting_balance(who: &T::AccountId) -> Option<BalanceOf<T>> {
+        let now = system::Pallet::<T>::block_number();
+        Vesting::<T>::get(who).map(|schedules| Self::locked_at(&schedules, now))
+    }
+
+    /// Lock funds `who` already owns under `schedule`.
+    ///
+    /// Other pallets can use it to vest funds they pay out.
+    pub fn add_vesting_schedule(who: &T::AccountId, schedule: VestingInfoOf<T>) -> DispatchResult {
+        Self::ensure_can_add_schedule(who, &schedule)?;
+        Vesting::<T>::mutate(who, |schedules| {
+            schedules.get_or_insert_with(Vec::new).push(schedule)
+        });
+        Self::do_vest(who)
+    }
+
+    fn ensure_can_add_schedule(who: &T::AccountId, schedule: &VestingInfoOf<T>) -> DispatchResult {
+        if !schedule.is_valid() {
+            return Err("Invalid vesting schedule");
+        }
+        if Self::vesting(who).len() >= T::MaxVestingSchedules::get() as usize {
+            return Err("Too many vesting schedules");
+        }
+        Ok(())
+    }
+
+    fn locked_at(schedules: &[VestingInfoOf<T>], n: T::BlockNumber) -> BalanceOf<T> {
+        schedules
+            .iter()
+            .fold(BalanceOf::<T>::zero(), |acc, schedule| {
+                acc.saturating_add(schedule.locked_at::<T::BlockNumberToBalance>(n))
+            })
+    }
+
+    /// Drop the schedules of `who` that have fully vested and lock what the others still lock.
+    fn do_vest(who: &T::AccountId) -> DispatchResult {
+        let mut schedules = Vesting::<T>::get(who).ok_or("Account is not vesting")?;
+        let now = system::Pallet::<T>::block_number();
+        schedules.retain(|schedule| !schedule.locked_at::<T::BlockNumberToBalance>(now).is_zero());
+
+        let unvested = Self::locked_at(&schedules, now);
+        if schedules.is_empty() {
+            Vesting::<T>::remove(who);
+            T::Currency::remove_lock(VESTING_ID, who);
+            system::Pallet::<T>::deposit_event(Event::VestingCompleted {
+                account: who.clone(),
+            });
+        } else {
+            Vesting::<T>::insert(who.clone(), schedules);
+            T::Currency::set_lock(VESTING_ID, who, unvested, WithdrawReasons::Transfer);
+            system::Pallet::<T>::deposit_event(Event::VestingUpdated {
+                account: who.clone(),
+                unvested,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl<T: Config> Hooks<T::BlockNumber> for Pallet<T> {}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        balances,
+        mock::TestConfig,
+        support::traits::{ConstU128, ConstU32, ConvertInto},
+        types,
+    };
+
+    use super::*;
+
+    impl Config for TestConfig {
+        type Currency = balances::Pallet<TestConfig>;
+        type BlockNumberToBalance = ConvertInto;
+        type MinVestedTransfer = ConstU128<10>;
+        type MaxVestingSchedules = ConstU32<2>;
+    }
+
+    type System = system::Pallet<TestConfig>;
+    type Balances = balances::Pallet<TestConfig>;
+    type Vesting = Pallet<TestConfig>;
+
+    fn run_to_block(n: types::BlockNumber) {
+        while System::block_number() < n {
+            System::inc_block_number();
+        }
+    }
+
+    fn transfer(from: &str, to: &str, amount: u128) -> DispatchResult {
+        Balances::new().transfer(from.to_string(), to.to_string(), amount)
+    }
+
+    #[test]
+    fn genesis_vesting_unlocks_linearly() {
+        let a = "A".to_string();
+        Balances::set_balance(&a, 100);
+        GenesisConfig::<TestConfig> {
+            vesting: vec![(a.clone(), 2, 10, 20)],
+        }
+        .build()
+        .unwrap();
+        assert_eq!(Vesting::vesting_balance(&a), Some(80));
+        assert_eq!(
+            transfer("A", "B", 21),
+            Err("Liquidity restrictions prevent withdrawal")
+        );
+
+        let mut vesting = Vesting::new();
+        run_to_block(5);
+        vesting.vest(a.clone()).unwrap();
+        assert_eq!(Vesting::vesting_balance(&a), Some(56));
+        transfer("A", "B", 44).unwrap();
+
+        run_to_block(12);
+        vesting.vest_other("B".to_string(), a.clone()).unwrap();
+        assert_eq!(Vesting::vesting_balance(&a), None);
+        assert!(Balances::locks(&a).is_empty());
+        assert_eq!(vesting.vest(a), Err("Account is not vesting"));
+    }
+
+    #[test]
+    fn vested_transfer_locks_funds_of_target() {
+        let a = "A".to_string();
+        let b = "B".to_string();
+        Balances::set_balance(&a, 100);
+        let mut vesting = Vesting::new();
+        let schedule = |locked| VestingInfo {
+            locked,
+            per_block: 5,
+            starting_block: 0,
+        };
+
+        assert_eq!(
+            vesting.vested_transfer(a.clone(), b.clone(), schedule(5)),
+            Err("Amount transferred is below the minimum vested transfer")
+        );
+        vesting
+            .vested_transfer(a.clone(), b.clone(), schedule(20))
+            .unwrap();
+        vesting
+            .vested_transfer(a.clone(), b.clone(), schedule(20))
+            .unwrap();
+        assert_eq!(
+            vesting.vested_transfer(a.clone(), b.clone(), schedule(20)),
+            Err("Too many vesting schedules")
+        );
+        assert_eq!(Balances::balance(&b), 40);
+        assert_eq!(
+            transfer("B", "C", 1),
+            Err("Liquidity restrictions prevent withdrawal")
+        );
+
+        run_to_block(2);
+        vesting.vest(b.clone()).unwrap();
+        transfer("B", "C", 20).unwrap();
+        assert_eq!(Balances::balance(&a), 60);
+    }
+
+    #[test]
+    fn merged_schedules_end_with_the_later_one() {
+        let a = "A".to_string();
+        Balances::set_balance(&a, 100);
+        Vesting::add_vesting_schedule(
+            &a,
+            VestingInfo {
+                locked: 30,
+                per_block: 10,
+                starting_block: 0,
+            },
+        )
+        .unwrap();
+        Vesting::add_vesting_schedule(
+            &a,
+            VestingInfo {
+                locked: 40,
+                per_block: 4,
+                starting_block: 2,
+            },
+        )
+        .unwrap();
+
+        let mut vesting = Vesting::new();
+        run_to_block(2);
+        assert_eq!(
+            vesting.merge_schedules(a.clone(), 0, 2),
+            Err("Schedule index out of bounds")
+        );
+        vesting.merge_schedules(a.clone(), 0, 1).unwrap();
+        assert_eq!(
+            Vesting::vesting(&a),
+            vec![VestingInfo {
+                locked: 50,
+                per_block: 5,
+                starting_block: 2,
+            }]
+        );
+        assert_eq!(Vesting::vesting_balance(&a), Some(50));
+
+        run_to_block(12);
+        vesting.vest(a.clone()).unwrap();
+        assert_eq!(Vesting::vesting_balance(&a), None);
+    }
+}